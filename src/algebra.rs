@@ -0,0 +1,268 @@
+//! Multiset operations between BBOWs, as methods and as
+//! the operators `+`, `-`, `|` and `&`.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::{Add, BitAnd, BitOr, Sub};
+
+use crate::{entry, Bbow};
+
+/// Find the value for `key` in `map`, inserting a default
+/// value under `copy(key)` first if needed.
+#[allow(clippy::ptr_arg)]
+fn entry_by<'m, 'a, 'o, V: Default>(
+    map: &'m mut BTreeMap<Cow<'a, str>, V>,
+    key: &Cow<'o, str>,
+    copy: impl Fn(&Cow<'o, str>) -> Cow<'a, str>,
+) -> &'m mut V {
+    if !map.contains_key(key.as_ref()) {
+        map.insert(copy(key), V::default());
+    }
+    map.get_mut(key.as_ref()).unwrap()
+}
+
+impl<'a> Bbow<'a> {
+    /// Make a BBOW following the rules of this one, with
+    /// the given `counts` and nothing else recorded.
+    pub(crate) fn with_counts(&self, counts: BTreeMap<Cow<'a, str>, usize>) -> Self {
+        Self {
+            counts,
+            surfaces: BTreeMap::new(),
+            analyzer: self.analyzer.clone(),
+            ngram: self.ngram,
+            texts: self.texts.as_ref().map(|_| Vec::new()),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    /// Add the counts of `other` to this BBOW, together
+    /// with its surface forms, and its positions if both
+    /// record them. Keys and texts this BBOW does not
+    /// already have are made by `copy`.
+    pub(crate) fn add_bag<'o>(
+        &mut self,
+        other: &Bbow<'o>,
+        copy: impl Fn(&Cow<'o, str>) -> Cow<'a, str>,
+    ) {
+        for (word, &count) in &other.counts {
+            *entry_by(&mut self.counts, word, &copy) += count;
+        }
+        for (word, forms) in &other.surfaces {
+            let surfaces = entry_by(&mut self.surfaces, word, &copy);
+            for (form, &count) in forms {
+                *entry_by(surfaces, form, &copy) += count;
+            }
+        }
+        if let (Some(ours), Some(texts)) = (&mut self.texts, &other.texts) {
+            let offset = ours.len();
+            ours.extend(texts.iter().map(&copy));
+            for (word, occurrences) in &other.positions {
+                let positions = entry_by(&mut self.positions, word, &copy);
+                positions.extend(occurrences.iter().map(|occurrence| {
+                    let mut occurrence = occurrence.clone();
+                    occurrence.text += offset;
+                    occurrence
+                }));
+            }
+        }
+    }
+
+    /// The multiset sum of this BBOW and `other`: each word
+    /// counted as often as in both together. Surface forms
+    /// are combined too, and so are positions when both
+    /// record them, the texts of `other` following those of
+    /// this BBOW. Otherwise, as for texts added before
+    /// [`Bbow::with_positions`], the words of `other` have
+    /// no recorded positions. Also available as `+`.
+    ///
+    /// The result follows the rules of this BBOW, and words
+    /// are combined as they are stored, so both should
+    /// follow the same rules. Keys borrowed by either BBOW
+    /// stay borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let a = Bbow::new().extend_from_text("a a b");
+    /// let b = Bbow::new().extend_from_text("a c");
+    /// let sum = &a + &b;
+    /// assert_eq!(vec![("a", 3), ("b", 1), ("c", 1)], sum.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn sum(&self, other: &Bbow<'a>) -> Bbow<'a> {
+        let mut sum = self.clone();
+        sum.add_bag(other, Cow::clone);
+        sum
+    }
+
+    /// The multiset union of this BBOW and `other`: each
+    /// word counted as often as in whichever has more of
+    /// it. As with the intersection and difference, surface
+    /// forms and positions are not kept, as they no longer
+    /// match the counts. Also available as `|`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let a = Bbow::new().extend_from_text("a a b");
+    /// let b = Bbow::new().extend_from_text("a c");
+    /// let union = &a | &b;
+    /// assert_eq!(vec![("a", 2), ("b", 1), ("c", 1)], union.iter().collect::<Vec<_>>());
+    /// let intersection = &a & &b;
+    /// assert_eq!(vec![("a", 1)], intersection.iter().collect::<Vec<_>>());
+    /// let difference = &a - &b;
+    /// assert_eq!(vec![("a", 1), ("b", 1)], difference.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn union(&self, other: &Bbow<'a>) -> Bbow<'a> {
+        let mut counts = self.counts.clone();
+        for (word, &count) in &other.counts {
+            let ours = entry(&mut counts, word);
+            *ours = count.max(*ours);
+        }
+        self.with_counts(counts)
+    }
+
+    /// The multiset intersection of this BBOW and `other`:
+    /// each word counted as often as in whichever has less
+    /// of it. Also available as `&`.
+    pub fn intersection(&self, other: &Bbow<'a>) -> Bbow<'a> {
+        let counts = self
+            .counts
+            .iter()
+            .filter_map(|(word, &count)| {
+                let theirs = *other.counts.get(word.as_ref())?;
+                Some((word.clone(), count.min(theirs)))
+            })
+            .collect();
+        self.with_counts(counts)
+    }
+
+    /// The multiset difference of this BBOW and `other`:
+    /// each word counted as many more times as it occurs in
+    /// this BBOW than in `other`, if any. Also available as
+    /// `-`.
+    pub fn difference(&self, other: &Bbow<'a>) -> Bbow<'a> {
+        let counts = self
+            .counts
+            .iter()
+            .filter_map(|(word, &count)| {
+                let theirs = other.counts.get(word.as_ref()).copied().unwrap_or(0);
+                let count = count.saturating_sub(theirs);
+                (count > 0).then(|| (word.clone(), count))
+            })
+            .collect();
+        self.with_counts(counts)
+    }
+}
+
+impl<'a> Add<&Bbow<'a>> for &Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn add(self, other: &Bbow<'a>) -> Bbow<'a> {
+        self.sum(other)
+    }
+}
+
+impl<'a> Add for Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn add(mut self, other: Bbow<'a>) -> Bbow<'a> {
+        self.add_bag(&other, Cow::clone);
+        self
+    }
+}
+
+impl<'a> BitOr<&Bbow<'a>> for &Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn bitor(self, other: &Bbow<'a>) -> Bbow<'a> {
+        self.union(other)
+    }
+}
+
+impl<'a> BitOr for Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn bitor(self, other: Bbow<'a>) -> Bbow<'a> {
+        self.union(&other)
+    }
+}
+
+impl<'a> BitAnd<&Bbow<'a>> for &Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn bitand(self, other: &Bbow<'a>) -> Bbow<'a> {
+        self.intersection(other)
+    }
+}
+
+impl<'a> BitAnd for Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn bitand(self, other: Bbow<'a>) -> Bbow<'a> {
+        self.intersection(&other)
+    }
+}
+
+impl<'a> Sub<&Bbow<'a>> for &Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn sub(self, other: &Bbow<'a>) -> Bbow<'a> {
+        self.difference(other)
+    }
+}
+
+impl<'a> Sub for Bbow<'a> {
+    type Output = Bbow<'a>;
+
+    fn sub(self, other: Bbow<'a>) -> Bbow<'a> {
+        self.difference(&other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sum_should_renumber_the_texts_of_the_other() {
+        let a = Bbow::new().with_positions().extend_from_text("one two");
+        let b = Bbow::new().with_positions().extend_from_text("two");
+
+        let sum = a + b;
+        assert_eq!(2, sum.match_count("two"));
+        let texts: Vec<_> = sum.occurrences("two").map(|o| o.text).collect();
+        assert_eq!(vec![0, 1], texts);
+        assert_eq!(Some("two"), sum.text(1));
+    }
+
+    #[test]
+    fn sum_should_not_start_recording_positions() {
+        let a = Bbow::new().extend_from_text("one two");
+        let b = Bbow::new().with_positions().extend_from_text("two");
+
+        let sum = &a + &b;
+        assert_eq!(0, sum.occurrences("two").count());
+        assert_eq!(None, sum.text(0));
+        let sum = &b + &a;
+        assert_eq!(1, sum.occurrences("two").count());
+        assert_eq!(2, sum.match_count("two"));
+    }
+
+    #[test]
+    fn keys_should_stay_borrowed_across_lifetimes() {
+        let long = String::from("shared long");
+        let a = Bbow::new().extend_from_text(&long);
+        {
+            let short = String::from("shared short");
+            let b = Bbow::new().extend_from_text(&short);
+
+            for bag in [&a | &b, &a + &b, &b - &a, &a & &b] {
+                assert!(bag.counts.keys().all(|key| matches!(key, Cow::Borrowed(_))));
+            }
+            assert_eq!(1, (&b - &a).len());
+            assert!((&a - &a).is_empty());
+        }
+    }
+}
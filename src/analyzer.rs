@@ -0,0 +1,289 @@
+//! The rules by which a text is reduced to a sequence of
+//! words.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::form::fold_diacritics;
+use crate::{CaseMode, NormalizationForm, PunctuationPolicy, Stemmer, Stopwords, Tokenizer};
+
+/// An `Analyzer` holds the rules that turn the tokens of a
+/// text into the words counted by a BBOW, and the keywords
+/// looked up in it. Bags built with the same analyzer share
+/// a vocabulary.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Analyzer {
+    punctuation: PunctuationPolicy,
+    case: CaseMode,
+    form: Option<NormalizationForm>,
+    fold_diacritics: bool,
+    stemmer: Option<Stemmer>,
+    stopwords: Stopwords,
+    /// The stopwords, normalized by the current rules.
+    stopword_forms: BTreeSet<String>,
+}
+
+/// A word found by an [`Analyzer`] in some text.
+#[derive(Debug, Clone)]
+pub(crate) struct Token<'t> {
+    /// The byte range of the word in the text.
+    pub(crate) span: Range<usize>,
+    /// The index of the word in the sequence of words found
+    /// in the text, counting stopwords and tokens with no
+    /// valid word.
+    pub(crate) ordinal: usize,
+    /// The normalized, unstemmed word.
+    pub(crate) surface: Cow<'t, str>,
+    /// The stem of the word, when stemming.
+    pub(crate) stem: Option<Cow<'t, str>>,
+}
+
+impl<'t> Token<'t> {
+    /// The key under which this word is counted.
+    pub(crate) fn key(&self) -> &Cow<'t, str> {
+        self.stem.as_ref().unwrap_or(&self.surface)
+    }
+
+    pub(crate) fn into_key(self) -> Cow<'t, str> {
+        self.stem.unwrap_or(self.surface)
+    }
+
+    /// Make a copy of this word that does not borrow from
+    /// its text.
+    pub(crate) fn into_owned(self) -> Token<'static> {
+        Token {
+            surface: Cow::Owned(self.surface.into_owned()),
+            stem: self.stem.map(|stem| Cow::Owned(stem.into_owned())),
+            ..self
+        }
+    }
+}
+
+/// The byte range of `part`, which must be a slice of
+/// `text`, within `text`.
+fn span_in(text: &str, part: &str) -> Range<usize> {
+    let start = (part.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+    assert!(
+        start <= text.len() && part.len() <= text.len() - start,
+        "token is not a slice of its text"
+    );
+    start..start + part.len()
+}
+
+/// Apply a normalization `stage` to a `word` produced by an
+/// earlier stage, borrowing from the original text when
+/// neither stage needed to copy.
+fn and_then<'w>(word: Cow<'w, str>, stage: impl FnOnce(&str) -> Cow<'_, str>) -> Cow<'w, str> {
+    match word {
+        Cow::Borrowed(word) => stage(word),
+        Cow::Owned(word) => {
+            let staged = match stage(&word) {
+                Cow::Borrowed(staged) if staged.len() == word.len() => None,
+                staged => Some(staged.into_owned()),
+            };
+            Cow::from(staged.unwrap_or(word))
+        }
+    }
+}
+
+impl Analyzer {
+    /// Make a new analyzer with the default rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the given policy for internal punctuation.
+    pub fn with_punctuation(mut self, punctuation: PunctuationPolicy) -> Self {
+        self.punctuation = punctuation;
+        self
+    }
+
+    /// The policy used for internal punctuation.
+    pub fn punctuation(&self) -> &PunctuationPolicy {
+        &self.punctuation
+    }
+
+    /// Use the given case normalization.
+    pub fn with_case(mut self, case: CaseMode) -> Self {
+        self.case = case;
+        self.with_stopword_forms()
+    }
+
+    /// The case normalization in use.
+    pub fn case(&self) -> CaseMode {
+        self.case
+    }
+
+    /// Normalize words to the given Unicode normalization
+    /// `form`.
+    pub fn with_normalization(mut self, form: NormalizationForm) -> Self {
+        self.form = Some(form);
+        self.with_stopword_forms()
+    }
+
+    /// The Unicode normalization form in use, if any.
+    pub fn normalization(&self) -> Option<NormalizationForm> {
+        self.form
+    }
+
+    /// Choose whether diacritics are removed from words, so
+    /// that "café" and "cafe" are the same word.
+    pub fn with_diacritic_folding(mut self, fold: bool) -> Self {
+        self.fold_diacritics = fold;
+        self.with_stopword_forms()
+    }
+
+    /// Are diacritics removed from words?
+    pub fn folds_diacritics(&self) -> bool {
+        self.fold_diacritics
+    }
+
+    /// Reduce words to their stems with the given
+    /// `stemmer`.
+    pub fn with_stemmer(mut self, stemmer: Stemmer) -> Self {
+        self.stemmer = Some(stemmer);
+        self
+    }
+
+    /// The stemmer in use, if any.
+    pub fn stemmer(&self) -> Option<Stemmer> {
+        self.stemmer
+    }
+
+    /// Leave the given `stopwords` out of the words
+    /// produced.
+    pub fn with_stopwords(mut self, stopwords: Stopwords) -> Self {
+        self.stopwords = stopwords;
+        self.with_stopword_forms()
+    }
+
+    /// The stopwords left out of the words produced.
+    pub fn stopwords(&self) -> &Stopwords {
+        &self.stopwords
+    }
+
+    /// Normalize the stopwords by the current rules.
+    fn with_stopword_forms(mut self) -> Self {
+        self.stopword_forms = self
+            .stopwords
+            .iter()
+            .map(|word| self.surface(word).into_owned())
+            .collect();
+        self
+    }
+
+    /// Is the normalized, unstemmed `word` a stopword?
+    pub(crate) fn is_stopword(&self, word: &str) -> bool {
+        self.stopword_forms.contains(word)
+    }
+
+    /// Reduce a single `word` to its normal form. Words
+    /// that are already normal are borrowed rather than
+    /// copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Analyzer;
+    /// assert_eq!("hello", Analyzer::new().normalize("Hello"));
+    /// ```
+    pub fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        self.stem(self.surface(word))
+    }
+
+    /// Normalize `word` without stemming it, producing the
+    /// surface form recorded for its stem.
+    pub(crate) fn surface<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        let mut word = self.case.apply(word);
+        if let Some(form) = self.form {
+            word = and_then(word, |word| form.apply(word));
+        }
+        if self.fold_diacritics {
+            word = and_then(word, fold_diacritics);
+        }
+        word
+    }
+
+    /// Stem a `word` previously normalized by
+    /// [`Analyzer::surface`].
+    pub(crate) fn stem<'w>(&self, word: Cow<'w, str>) -> Cow<'w, str> {
+        match self.stemmer {
+            Some(stemmer) => and_then(word, |word| stemmer.stem(word)),
+            None => word,
+        }
+    }
+
+    /// Split `text` into tokens with the given `tokenizer`
+    /// and produce the sequence of normalized words they
+    /// contain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tokenizer produces a token that is not
+    /// a slice of `text`, as required by [`Tokenizer`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Analyzer, WhitespaceTokenizer};
+    /// let analyzer = Analyzer::new();
+    /// let words: Vec<_> = analyzer
+    ///     .words(&WhitespaceTokenizer, "It ain't over.")
+    ///     .collect();
+    /// assert_eq!(vec!["it", "over"], words);
+    /// ```
+    pub fn words<'s, 't, T: Tokenizer>(
+        &'s self,
+        tokenizer: &'s T,
+        text: &'t str,
+    ) -> impl Iterator<Item = Cow<'t, str>> + 's
+    where
+        't: 's,
+    {
+        self.analyze(tokenizer, text).flatten().map(Token::into_key)
+    }
+
+    /// Split `text` into tokens with the given `tokenizer`
+    /// and produce the sequence of words they contain,
+    /// with their positions. Where a token contains no valid
+    /// word, or a word is a stopword, `None` is produced
+    /// instead, so that the words on either side are not
+    /// taken as consecutive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tokenizer produces a token that is not
+    /// a slice of `text`.
+    pub(crate) fn analyze<'s, 't, T: Tokenizer>(
+        &'s self,
+        tokenizer: &'s T,
+        text: &'t str,
+    ) -> impl Iterator<Item = Option<Token<'t>>> + 's
+    where
+        't: 's,
+    {
+        tokenizer
+            .tokens(text)
+            .flat_map(|token| {
+                let mut words = self.punctuation.words(token).peekable();
+                let dropped = words.peek().is_none();
+                dropped.then_some(None).into_iter().chain(words.map(Some))
+            })
+            .enumerate()
+            .map(move |(ordinal, word)| {
+                let word = word?;
+                let surface = self.surface(word);
+                if self.is_stopword(&surface) {
+                    return None;
+                }
+                let stem = self.stemmer.is_some().then(|| self.stem(surface.clone()));
+                Some(Token {
+                    span: span_in(text, word),
+                    ordinal,
+                    surface,
+                    stem,
+                })
+            })
+    }
+}
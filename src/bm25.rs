@@ -0,0 +1,164 @@
+//! Ranked retrieval of the documents of a corpus by the
+//! Okapi BM25 and BM25+ scoring functions.
+
+use std::collections::BTreeMap;
+
+use crate::Corpus;
+
+/// The parameters of the BM25 scoring function.
+///
+/// The score of a document for a query is the sum, over
+/// the words of the query, of
+///
+/// ```text
+/// idf * (tf * (k1 + 1) / (tf + k1 * (1 - b + b * dl / avgdl)) + delta)
+/// ```
+///
+/// where `tf` is the number of occurrences of the word in
+/// the document, `dl` the length of the document and
+/// `avgdl` the mean length of the documents. The `idf` is
+/// `ln(1 + (n - df + 0.5) / (df + 0.5))`, for `n` documents
+/// of which `df` contain the word. A `delta` of 0 gives
+/// BM25; a positive `delta` gives BM25+, which keeps long
+/// documents containing the word from scoring as low as
+/// those without it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bm25 {
+    k1: f64,
+    b: f64,
+    delta: f64,
+}
+
+impl Default for Bm25 {
+    fn default() -> Self {
+        Self {
+            k1: 1.2,
+            b: 0.75,
+            delta: 0.0,
+        }
+    }
+}
+
+impl Bm25 {
+    /// Make a new BM25 scorer with the usual parameters
+    /// `k1 = 1.2` and `b = 0.75`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make a new BM25+ scorer with the usual parameters
+    /// and `delta = 1`.
+    pub fn plus() -> Self {
+        Self::default().with_delta(1.0)
+    }
+
+    /// Set the saturation of repeated occurrences: the
+    /// higher `k1`, the more each further occurrence of a
+    /// word counts.
+    pub fn with_k1(mut self, k1: f64) -> Self {
+        self.k1 = k1;
+        self
+    }
+
+    /// Set the strength of document length normalization,
+    /// from 0 (none) to 1 (full).
+    pub fn with_b(mut self, b: f64) -> Self {
+        self.b = b;
+        self
+    }
+
+    /// Set the lower bound added to the score of each word
+    /// present in a document.
+    pub fn with_delta(mut self, delta: f64) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    fn score(&self, count: usize, length: f64, average_length: f64) -> f64 {
+        let count = count as f64;
+        let norm = 1.0 - self.b + self.b * length / average_length;
+        count * (self.k1 + 1.0) / (count + self.k1 * norm) + self.delta
+    }
+}
+
+impl<'a, Id: Ord + Clone> Corpus<'a, Id> {
+    /// Rank the documents of this corpus by their `scorer`
+    /// score for the `query`, returning the `k` best with
+    /// their scores, best first. Documents with equal
+    /// scores are in order of their ids. The query is
+    /// parsed by the rules of the corpus, as by
+    /// [`Bbow::extend_from_text`](crate::Bbow::extend_from_text);
+    /// a word occurring twice in it counts twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bm25, Corpus};
+    /// let corpus = Corpus::new()
+    ///     .insert_document("a", "the cat sat on the mat")
+    ///     .insert_document("b", "the dog sat")
+    ///     .insert_document("c", "a cat and a dog and a cat");
+    /// let ranked = corpus.search("Cat!", &Bm25::new(), 2);
+    /// let ids: Vec<_> = ranked.iter().map(|&(id, _)| *id).collect();
+    /// assert_eq!(vec!["c", "a"], ids);
+    /// ```
+    pub fn search(&self, query: &str, scorer: &Bm25, k: usize) -> Vec<(&Id, f64)> {
+        let query = self.template.clone().extend_from_text(query);
+        let n = self.len() as f64;
+        let average_length = self.average_length();
+
+        let mut scores: BTreeMap<&Id, f64> = BTreeMap::new();
+        for (word, &times) in &query.counts {
+            let Some(documents) = self.postings.get(word.as_ref()) else {
+                continue;
+            };
+            let df = documents.len() as f64;
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+            for (id, &count) in documents {
+                let length = self.document_length(id) as f64;
+                let score = idf * scorer.score(count, length, average_length);
+                *scores.entry(id).or_insert(0.0) += times as f64 * score;
+            }
+        }
+
+        let mut ranked: Vec<(&Id, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|(a, x), (b, y)| y.total_cmp(x).then_with(|| a.cmp(b)));
+        ranked.truncate(k);
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shorter_documents_should_rank_higher() {
+        let corpus = Corpus::new()
+            .insert_document(1, "cat dog dog dog dog dog")
+            .insert_document(2, "cat dog")
+            .insert_document(3, "bird");
+
+        let ranked = corpus.search("cat", &Bm25::new(), 10);
+        assert_eq!(
+            vec![2, 1],
+            ranked.iter().map(|&(&id, _)| id).collect::<Vec<_>>()
+        );
+
+        let flat = corpus.search("cat", &Bm25::new().with_b(0.0), 10);
+        assert_eq!(flat[0].1, flat[1].1);
+    }
+
+    #[test]
+    fn delta_should_raise_every_match() {
+        let corpus = Corpus::new()
+            .insert_document(1, "cat")
+            .insert_document(2, "dog");
+
+        let plain = corpus.search("cat", &Bm25::new(), 1)[0].1;
+        let plus = corpus.search("cat", &Bm25::plus(), 1)[0].1;
+        let idf = (1.0f64 + 1.5 / 1.5).ln();
+        assert!((plus - plain - idf).abs() < 1e-12);
+        assert!(corpus.search("bird", &Bm25::plus(), 1).is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+//! Ingestion of text given as bytes that may not be valid
+//! UTF-8.
+
+use std::collections::VecDeque;
+
+use crate::{Bbow, InvalidUtf8, WhitespaceTokenizer};
+
+/// What to do with byte sequences that are not valid UTF-8.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Reject the input.
+    #[default]
+    Strict,
+    /// Replace each invalid sequence with U+FFFD, the
+    /// replacement character. As it is not a letter, it is
+    /// treated like punctuation.
+    Replace,
+    /// Leave out each invalid sequence, joining the text on
+    /// either side.
+    Skip,
+}
+
+/// The byte offset just after the last whitespace in
+/// `text`, or 0 if there is none.
+pub(crate) fn after_last_whitespace(text: &str) -> usize {
+    text.char_indices()
+        .rev()
+        .find(|&(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+impl<'a> Bbow<'a> {
+    /// Parse the `target` bytes as UTF-8 text, handling
+    /// invalid sequences according to the `policy`, and add
+    /// the sequence of valid words contained in it to this
+    /// BBOW. Words are found as by
+    /// [`Bbow::extend_from_text`].
+    ///
+    /// The words and n-grams found are those of the text
+    /// with its invalid sequences handled. Words in valid
+    /// stretches of the input are borrowed from it, and only
+    /// words touching an invalid sequence are copied, unless
+    /// positions are recorded: the text is then copied
+    /// whole, as a single text, as by
+    /// [`Bbow::with_positions`].
+    ///
+    /// # Errors
+    ///
+    /// Fails, with the offset of the first invalid
+    /// sequence, if the input is not valid UTF-8 and the
+    /// policy is [`Utf8Policy::Strict`]. This BBOW is then
+    /// left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bbow, Utf8Policy};
+    /// let input = b"ca\xfff\xfe latte caf\xc3\xa9";
+    /// let mut bbow = Bbow::new();
+    /// let error = bbow.extend_from_bytes(input, Utf8Policy::Strict).unwrap_err();
+    /// assert_eq!(2, error.offset());
+    /// assert_eq!(0, bbow.len());
+    ///
+    /// bbow.extend_from_bytes(input, Utf8Policy::Skip).unwrap();
+    /// assert_eq!(1, bbow.match_count("caf"));
+    /// assert_eq!(1, bbow.match_count("café"));
+    ///
+    /// let mut bbow = Bbow::new();
+    /// bbow.extend_from_bytes(input, Utf8Policy::Replace).unwrap();
+    /// assert_eq!(0, bbow.match_count("caf"));
+    /// assert_eq!(2, bbow.len());
+    /// ```
+    pub fn extend_from_bytes(
+        &mut self,
+        target: &'a [u8],
+        policy: Utf8Policy,
+    ) -> Result<(), InvalidUtf8> {
+        match std::str::from_utf8(target) {
+            Ok(text) => *self = std::mem::take(self).extend_from_text(text),
+            Err(error) if policy == Utf8Policy::Strict => {
+                return Err(InvalidUtf8::new(error.valid_up_to()))
+            }
+            Err(_) => *self = std::mem::take(self).extend_from_invalid(target, policy),
+        }
+        Ok(())
+    }
+
+    /// Add the words of `target`, which is not valid UTF-8,
+    /// to this BBOW, replacing or skipping its invalid
+    /// sequences according to the `policy`.
+    fn extend_from_invalid(self, target: &'a [u8], policy: Utf8Policy) -> Self {
+        if self.texts.is_some() {
+            let mut text = String::with_capacity(target.len());
+            for chunk in target.utf8_chunks() {
+                text.push_str(chunk.valid());
+                if !chunk.invalid().is_empty() && policy == Utf8Policy::Replace {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                }
+            }
+            return self.extend_copied(&text, &mut VecDeque::new());
+        }
+
+        let mut bbow = self;
+        let mut window = VecDeque::new();
+        let mut broken: Option<String> = None;
+        for chunk in target.utf8_chunks() {
+            let mut rest = chunk.valid();
+            if let Some(word) = &mut broken {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                word.push_str(&rest[..end]);
+                rest = &rest[end..];
+                if !rest.is_empty() {
+                    bbow = bbow.extend_copied(&std::mem::take(word), &mut window);
+                    broken = None;
+                }
+            }
+
+            if chunk.invalid().is_empty() {
+                if broken.is_none() && !rest.is_empty() {
+                    bbow = bbow.extend_continuing(rest, &WhitespaceTokenizer, &mut window);
+                }
+                continue;
+            }
+            let word = match &mut broken {
+                Some(word) => word,
+                None => {
+                    let start = after_last_whitespace(rest);
+                    if start > 0 {
+                        bbow = bbow.extend_continuing(
+                            &rest[..start],
+                            &WhitespaceTokenizer,
+                            &mut window,
+                        );
+                    }
+                    broken.insert(rest[start..].to_string())
+                }
+            };
+            if policy == Utf8Policy::Replace {
+                word.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+        if let Some(word) = broken {
+            bbow = bbow.extend_copied(&word, &mut window);
+        }
+        bbow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    /// Parse `input` into `bbow` under the `policy`.
+    fn decode<'a>(mut bbow: Bbow<'a>, input: &'a [u8], policy: Utf8Policy) -> Bbow<'a> {
+        bbow.extend_from_bytes(input, policy).unwrap();
+        bbow
+    }
+
+    #[test]
+    fn valid_stretches_should_stay_borrowed() {
+        let input = b"one t\xffwo\xff three\n\xfffour five";
+        let bbow = decode(Bbow::new(), input, Utf8Policy::Skip);
+
+        let words: Vec<_> = bbow.iter().collect();
+        assert_eq!(
+            vec![
+                ("five", 1),
+                ("four", 1),
+                ("one", 1),
+                ("three", 1),
+                ("two", 1)
+            ],
+            words
+        );
+        for (word, borrowed) in [
+            ("one", true),
+            ("two", false),
+            ("three", true),
+            ("four", false),
+        ] {
+            let (key, _) = bbow.counts.get_key_value(word).unwrap();
+            assert_eq!(borrowed, matches!(key, Cow::Borrowed(_)), "{word}");
+        }
+    }
+
+    #[test]
+    fn ngrams_should_span_invalid_sequences() {
+        let input = b"one two th\xffree four five";
+        let text = "one two three four five";
+        for rules in [Bbow::new(), Bbow::new().with_positions()] {
+            let rules = rules.with_ngrams(2);
+            let bbow = decode(rules.clone(), input, Utf8Policy::Skip);
+            let expected = rules.extend_from_text(text);
+            assert_eq!(
+                expected.iter().collect::<Vec<_>>(),
+                bbow.iter().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                expected.occurrences("three four").collect::<Vec<_>>(),
+                bbow.occurrences("three four").collect::<Vec<_>>()
+            );
+        }
+
+        let bbow = decode(Bbow::new().with_ngrams(2), input, Utf8Policy::Skip);
+        let (key, _) = bbow.counts.get_key_value("four five").unwrap();
+        assert!(matches!(key, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn a_trailing_invalid_sequence_should_end_its_word() {
+        let bbow = decode(Bbow::new(), b"ab\xe2\x82", Utf8Policy::Skip);
+        assert_eq!(1, bbow.match_count("ab"));
+    }
+
+    #[test]
+    fn rejected_bytes_should_leave_the_bag_unchanged() {
+        let mut bbow = Bbow::new().with_positions().extend_from_text("keep me");
+        let error = bbow
+            .extend_from_bytes(b"bad \xff", Utf8Policy::Strict)
+            .unwrap_err();
+        assert_eq!(4, error.offset());
+        assert_eq!(1, bbow.match_count("keep"));
+        assert_eq!(0, bbow.match_count("bad"));
+        assert_eq!(None, bbow.text(1));
+    }
+}
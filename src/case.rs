@@ -0,0 +1,118 @@
+//! Case normalization of words.
+
+use std::borrow::Cow;
+
+/// A locale with its own lowercasing rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    /// Turkish: `I` lowercases to dotless `ı`, and `İ`
+    /// lowercases to `i`.
+    Turkish,
+    /// Azerbaijani: the same dotted and dotless i rules as
+    /// Turkish.
+    Azerbaijani,
+}
+
+/// How the case of words is normalized.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Bbow, CaseMode};
+/// let bbow = Bbow::new()
+///     .with_case(CaseMode::Fold)
+///     .extend_from_text("Straße STRASSE");
+/// assert_eq!(2, bbow.match_count("strasse"));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CaseMode {
+    /// Simple Unicode lowercasing, as by
+    /// [`str::to_lowercase`].
+    #[default]
+    Lowercase,
+    /// Full Unicode case folding: for example, "Straße"
+    /// and "STRASSE" both fold to "strasse", and Greek
+    /// final sigma folds to ordinary sigma.
+    Fold,
+    /// Lowercasing following the rules of the given
+    /// locale.
+    Locale(Locale),
+    /// Leave the case of words unchanged.
+    Preserve,
+}
+
+fn has_uppercase(word: &str) -> bool {
+    word.chars().any(char::is_uppercase)
+}
+
+/// Lowercase `word` with the Turkic dotted and dotless i
+/// rules.
+fn turkic_lowercase(word: &str) -> String {
+    let mut mapped = String::with_capacity(word.len());
+    let mut chars = word.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            'I' if chars.peek() == Some(&'\u{307}') => {
+                chars.next();
+                mapped.push('i');
+            }
+            'I' => mapped.push('ı'),
+            'İ' => mapped.push('i'),
+            c => mapped.push(c),
+        }
+    }
+    mapped.to_lowercase()
+}
+
+impl CaseMode {
+    /// Normalize the case of `word`. Words that are
+    /// unchanged by normalization are borrowed rather than
+    /// copied.
+    pub fn apply<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        match self {
+            CaseMode::Lowercase if has_uppercase(word) => Cow::from(word.to_lowercase()),
+            CaseMode::Fold => {
+                let folded = caseless::default_case_fold_str(word);
+                if folded == word {
+                    Cow::from(word)
+                } else {
+                    Cow::from(folded)
+                }
+            }
+            CaseMode::Locale(Locale::Turkish | Locale::Azerbaijani) if has_uppercase(word) => {
+                Cow::from(turkic_lowercase(word))
+            }
+            _ => Cow::from(word),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_should_merge_sharp_s_and_final_sigma() {
+        assert_eq!("strasse", CaseMode::Fold.apply("Straße"));
+        assert_eq!("strasse", CaseMode::Fold.apply("STRASSE"));
+        assert_eq!("σοφοσ", CaseMode::Fold.apply("σοφος"));
+        assert!(matches!(CaseMode::Fold.apply("plain"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn turkish_should_use_dotless_i() {
+        let turkish = CaseMode::Locale(Locale::Turkish);
+
+        assert_eq!("ıstanbul", turkish.apply("ISTANBUL"));
+        assert_eq!("istanbul", turkish.apply("İSTANBUL"));
+        assert_eq!("istanbul", turkish.apply("I\u{307}stanbul"));
+    }
+
+    #[test]
+    fn preserve_should_borrow() {
+        assert!(matches!(
+            CaseMode::Preserve.apply("Hello"),
+            Cow::Borrowed("Hello")
+        ));
+    }
+}
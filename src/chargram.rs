@@ -0,0 +1,196 @@
+//! Bags of character n-grams, for language identification
+//! and fuzzy matching.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::{Analyzer, Tokenizer, WhitespaceTokenizer};
+
+/// Each key in this struct's map is a character n-gram of
+/// the words of some in-memory text document, as found by
+/// the rules of an [`Analyzer`]. The corresponding value is
+/// the count of occurrences.
+///
+/// By default each word is padded with `_` at both ends, so
+/// that n-grams at the start and end of words are
+/// distinguished from those in the middle. N-grams that lie
+/// within a word borrowed from the text are borrowed too.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::CharNgrams;
+/// let grams = CharNgrams::new(3).extend_from_text("Cats scatter.");
+/// assert_eq!(11, grams.count());
+/// assert_eq!(2, grams.match_count("cat"));
+/// assert_eq!(1, grams.match_count("_ca"));
+/// assert_eq!(1, grams.match_count("ts_"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CharNgrams<'a> {
+    counts: BTreeMap<Cow<'a, str>, usize>,
+    analyzer: Analyzer,
+    n: usize,
+    padding: Option<char>,
+}
+
+impl<'a> CharNgrams<'a> {
+    /// Make a new empty bag of character `n`-grams.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn new(n: usize) -> Self {
+        assert!(n > 0, "n-grams must contain at least one character");
+        Self {
+            counts: BTreeMap::new(),
+            analyzer: Analyzer::default(),
+            n,
+            padding: Some('_'),
+        }
+    }
+
+    /// Use the rules of the given `analyzer` to find the
+    /// words of texts subsequently added to this bag.
+    pub fn with_analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    /// Pad words with the given character at both ends
+    /// before taking their n-grams, or do not pad them if
+    /// `padding` is `None`.
+    pub fn with_padding(mut self, padding: Option<char>) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Parse the `target` text and add the n-grams of the
+    /// sequence of valid words contained in it to this bag.
+    /// Words are found as by
+    /// [`Bbow::extend_from_text`](crate::Bbow::extend_from_text).
+    pub fn extend_from_text(self, target: &'a str) -> Self {
+        self.extend_with_tokenizer(target, &WhitespaceTokenizer)
+    }
+
+    /// Split the `target` text into tokens using the given
+    /// `tokenizer`, and add the n-grams of the sequence of
+    /// valid words among them to this bag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tokenizer produces a token that is not
+    /// a slice of `target`, as required by [`Tokenizer`].
+    pub fn extend_with_tokenizer<T: Tokenizer>(mut self, target: &'a str, tokenizer: &T) -> Self {
+        let analyzer = std::mem::take(&mut self.analyzer);
+        for word in analyzer.words(tokenizer, target) {
+            self.insert_word(word);
+        }
+        self.analyzer = analyzer;
+
+        self
+    }
+
+    /// Count the n-grams of a single `word`.
+    fn insert_word(&mut self, word: Cow<'a, str>) {
+        let bounds: Vec<usize> = word
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(word.len()))
+            .collect();
+        let chars = bounds.len() - 1;
+        let pad = usize::from(self.padding.is_some());
+        let total = chars + 2 * pad;
+        if total < self.n {
+            return;
+        }
+
+        for start in 0..=total - self.n {
+            let end = start + self.n;
+            let gram = match &word {
+                Cow::Borrowed(word) if start >= pad && end <= chars + pad => {
+                    Cow::from(&word[bounds[start - pad]..bounds[end - pad]])
+                }
+                _ => {
+                    let padding = self.padding.into_iter();
+                    let padded = padding.clone().chain(word.chars()).chain(padding);
+                    Cow::from(padded.skip(start).take(self.n).collect::<String>())
+                }
+            };
+            self.counts
+                .entry(gram)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+    }
+
+    /// Report the number of occurrences of the given `gram`
+    /// in this bag. The gram is normalized by the rules of
+    /// the bag's analyzer, except that it is not stemmed.
+    pub fn match_count(&self, gram: &str) -> usize {
+        let gram = self.analyzer.surface(gram);
+        *self.counts.get(gram.as_ref()).unwrap_or(&0usize)
+    }
+
+    /// The distinct n-grams in this bag, in order.
+    pub fn grams(&self) -> impl Iterator<Item = &str> {
+        self.counts.keys().map(|gram| gram.as_ref())
+    }
+
+    /// The distinct n-grams in this bag, in order, each
+    /// with its number of occurrences.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.counts
+            .iter()
+            .map(|(gram, &count)| (gram.as_ref(), count))
+    }
+
+    /// The length of the n-grams in this bag.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Count the overall number of n-grams contained in
+    /// this bag: multiple occurrences are considered
+    /// separate.
+    pub fn count(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Count the number of unique n-grams contained in this
+    /// bag, not considering number of occurrences.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Is this bag empty?
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inner_grams_should_be_borrowed() {
+        let grams = CharNgrams::new(2).extend_from_text("héllo");
+
+        let (inner, _) = grams.counts.get_key_value("él").unwrap();
+        assert!(matches!(inner, Cow::Borrowed(_)));
+        let (edge, _) = grams.counts.get_key_value("_h").unwrap();
+        assert!(matches!(edge, Cow::Owned(_)));
+        assert_eq!(6, grams.count());
+    }
+
+    #[test]
+    fn unpadded_short_words_should_have_no_grams() {
+        let grams = CharNgrams::new(3)
+            .with_padding(None)
+            .extend_from_text("a an ant");
+
+        assert_eq!(1, grams.len());
+        assert_eq!(1, grams.match_count("ANT"));
+    }
+}
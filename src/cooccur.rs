@@ -0,0 +1,231 @@
+//! Counting of word pairs that occur near each other, as
+//! input for measures such as pointwise mutual information
+//! and for training word embeddings.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{entry, Analyzer, Tokenizer, WhitespaceTokenizer};
+
+/// How much a pair of words counts, by their distance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Weighting {
+    /// Every pair within the window counts 1.
+    #[default]
+    Uniform,
+    /// A pair of words `d` words apart counts `1 / d`.
+    InverseDistance,
+}
+
+impl Weighting {
+    fn weight(self, distance: usize) -> f64 {
+        match self {
+            Weighting::Uniform => 1.0,
+            Weighting::InverseDistance => 1.0 / distance as f64,
+        }
+    }
+}
+
+/// Counts of the ordered pairs of words that occur within
+/// a window of each other in some in-memory text
+/// documents, as found by the rules of an [`Analyzer`].
+/// The occurrences of individual words are counted too.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::Cooccurrences;
+/// let pairs = Cooccurrences::new(2).extend_from_text("New York is in New York.");
+/// assert_eq!(2.0, pairs.pair_count("new", "york"));
+/// assert_eq!(0.0, pairs.pair_count("york", "new"));
+/// assert_eq!(2.0, pairs.cooccurrence("in", "york"));
+/// assert_eq!(2, pairs.word_count("york"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cooccurrences<'a> {
+    pairs: BTreeMap<Cow<'a, str>, BTreeMap<Cow<'a, str>, f64>>,
+    words: BTreeMap<Cow<'a, str>, usize>,
+    analyzer: Analyzer,
+    window: usize,
+    weighting: Weighting,
+}
+
+impl<'a> Cooccurrences<'a> {
+    /// Make a new empty counter of the pairs of words at
+    /// most `window` words apart.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "the window must contain at least one word");
+        Self {
+            pairs: BTreeMap::new(),
+            words: BTreeMap::new(),
+            analyzer: Analyzer::default(),
+            window,
+            weighting: Weighting::default(),
+        }
+    }
+
+    /// Use the rules of the given `analyzer` to find the
+    /// words of texts subsequently added to this counter.
+    pub fn with_analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    /// Weight the pairs subsequently counted by the given
+    /// `weighting`.
+    pub fn with_weighting(mut self, weighting: Weighting) -> Self {
+        self.weighting = weighting;
+        self
+    }
+
+    /// Parse the `target` text and count the pairs of
+    /// valid words contained in it. Words are found as by
+    /// [`Bbow::extend_from_text`](crate::Bbow::extend_from_text).
+    /// Pairs do not span texts.
+    pub fn extend_from_text(self, target: &'a str) -> Self {
+        self.extend_with_tokenizer(target, &WhitespaceTokenizer)
+    }
+
+    /// Split the `target` text into tokens using the given
+    /// `tokenizer`, and count the pairs of valid words
+    /// among them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tokenizer produces a token that is not
+    /// a slice of `target`, as required by [`Tokenizer`].
+    pub fn extend_with_tokenizer<T: Tokenizer>(mut self, target: &'a str, tokenizer: &T) -> Self {
+        let mut recent: VecDeque<Cow<'a, str>> = VecDeque::with_capacity(self.window);
+        for word in self.analyzer.words(tokenizer, target) {
+            for (distance, earlier) in recent.iter().rev().enumerate() {
+                let weight = self.weighting.weight(distance + 1);
+                *entry(entry(&mut self.pairs, earlier), &word) += weight;
+            }
+            *entry(&mut self.words, &word) += 1;
+
+            if recent.len() == self.window {
+                recent.pop_front();
+            }
+            recent.push_back(word);
+        }
+
+        self
+    }
+
+    /// Report the weighted count of occurrences of `first`
+    /// followed, within the window, by `second`. Both words
+    /// are normalized by the rules of the analyzer.
+    pub fn pair_count(&self, first: &str, second: &str) -> f64 {
+        let first = self.analyzer.normalize(first);
+        let second = self.analyzer.normalize(second);
+        self.pairs
+            .get(first.as_ref())
+            .and_then(|seconds| seconds.get(second.as_ref()))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Report the weighted count of occurrences of `a` and
+    /// `b` within the window of each other, in either
+    /// order.
+    pub fn cooccurrence(&self, a: &str, b: &str) -> f64 {
+        let forward = self.pair_count(a, b);
+        if self.analyzer.normalize(a) == self.analyzer.normalize(b) {
+            forward
+        } else {
+            forward + self.pair_count(b, a)
+        }
+    }
+
+    /// Report the number of occurrences of `word`.
+    pub fn word_count(&self, word: &str) -> usize {
+        let word = self.analyzer.normalize(word);
+        *self.words.get(word.as_ref()).unwrap_or(&0usize)
+    }
+
+    /// The pointwise mutual information of `a` and `b`:
+    /// the log (base 2) of the probability of the pair,
+    /// in either order, over the product of the
+    /// probabilities of the words. Reports `None` if
+    /// either word or the pair never occurs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Cooccurrences;
+    /// let pairs = Cooccurrences::new(1).extend_from_text("a b a b c d");
+    /// assert!(pairs.pmi("a", "b").unwrap() > pairs.pmi("b", "c").unwrap());
+    /// assert_eq!(None, pairs.pmi("a", "d"));
+    /// ```
+    pub fn pmi(&self, a: &str, b: &str) -> Option<f64> {
+        let pair = self.cooccurrence(a, b);
+        let (count_a, count_b) = (self.word_count(a), self.word_count(b));
+        if pair == 0.0 || count_a == 0 || count_b == 0 {
+            return None;
+        }
+
+        let total_words = self.words.values().sum::<usize>() as f64;
+        let p_pair = pair / self.total();
+        let p_a = count_a as f64 / total_words;
+        let p_b = count_b as f64 / total_words;
+        Some((p_pair / (p_a * p_b)).log2())
+    }
+
+    /// The ordered pairs counted, in order, each with its
+    /// weighted count.
+    pub fn pairs(&self) -> impl Iterator<Item = ((&str, &str), f64)> {
+        self.pairs.iter().flat_map(|(first, seconds)| {
+            seconds
+                .iter()
+                .map(move |(second, &count)| ((first.as_ref(), second.as_ref()), count))
+        })
+    }
+
+    /// The sum of the weighted counts of all pairs.
+    pub fn total(&self) -> f64 {
+        self.pairs.values().flat_map(BTreeMap::values).sum()
+    }
+
+    /// Count the number of distinct ordered pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.values().map(BTreeMap::len).sum()
+    }
+
+    /// Are there no pairs?
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_distance_should_weight_far_pairs_less() {
+        let pairs = Cooccurrences::new(3)
+            .with_weighting(Weighting::InverseDistance)
+            .extend_from_text("a b c d");
+
+        assert_eq!(1.0, pairs.pair_count("a", "b"));
+        assert_eq!(0.5, pairs.pair_count("a", "c"));
+        assert_eq!(1.0 / 3.0, pairs.pair_count("a", "d"));
+        assert_eq!(6, pairs.len());
+    }
+
+    #[test]
+    fn pairs_should_not_span_texts() {
+        let pairs = Cooccurrences::new(2)
+            .extend_from_text("a b")
+            .extend_from_text("c");
+
+        assert_eq!(0.0, pairs.cooccurrence("b", "c"));
+        let (_, first) = pairs.pairs.first_key_value().unwrap();
+        let (key, _) = first.first_key_value().unwrap();
+        assert!(matches!(key, Cow::Borrowed("b")));
+    }
+}
@@ -0,0 +1,212 @@
+//! A multi-document inverted index built from one BBOW per
+//! document.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::{entry, Analyzer, Bbow};
+
+/// A collection of documents, each identified by an `Id`
+/// and reduced to a [`Bbow`], together with an inverted
+/// index from each word to the documents containing it.
+///
+/// Documents added as text are counted by a clone of a
+/// template BBOW, so that every document shares its rules:
+/// see [`Corpus::with_template`]. Words looked up in the
+/// corpus are normalized by the same rules.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::Corpus;
+/// let corpus = Corpus::new()
+///     .insert_document("a", "The cat sat on the mat.")
+///     .insert_document("b", "The dog sat.")
+///     .insert_document("c", "Cats and dogs.");
+/// assert_eq!(2, corpus.document_frequency("sat"));
+/// let postings: Vec<_> = corpus.postings("The").collect();
+/// assert_eq!(vec![(&"a", 2), (&"b", 1)], postings);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Corpus<'a, Id> {
+    pub(crate) template: Bbow<'a>,
+    documents: BTreeMap<Id, Bbow<'a>>,
+    pub(crate) postings: BTreeMap<Cow<'a, str>, BTreeMap<Id, usize>>,
+    lengths: BTreeMap<Id, usize>,
+    total_length: usize,
+}
+
+impl<'a, Id: Ord + Clone> Default for Corpus<'a, Id> {
+    fn default() -> Self {
+        Self {
+            template: Bbow::default(),
+            documents: BTreeMap::new(),
+            postings: BTreeMap::new(),
+            lengths: BTreeMap::new(),
+            total_length: 0,
+        }
+    }
+}
+
+impl<'a, Id: Ord + Clone> Corpus<'a, Id> {
+    /// Make a new empty corpus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count documents subsequently added as text with a
+    /// clone of the given `template` BBOW, and normalize
+    /// looked-up words by its rules. The template should be
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bbow, Corpus, Stemmer};
+    /// let corpus = Corpus::new()
+    ///     .with_template(Bbow::new().with_stemmer(Stemmer::Porter2))
+    ///     .insert_document(1, "running")
+    ///     .insert_document(2, "runs");
+    /// assert_eq!(2, corpus.document_frequency("run"));
+    /// ```
+    pub fn with_template(mut self, template: Bbow<'a>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// The rules by which words are found and normalized.
+    pub fn analyzer(&self) -> &Analyzer {
+        self.template.analyzer()
+    }
+
+    /// Parse the `text` of the document `id` and add it to
+    /// this corpus, replacing any document with the same
+    /// `id`.
+    pub fn insert_document(self, id: Id, text: &'a str) -> Self {
+        let bbow = self.template.clone().extend_from_text(text);
+        self.insert_bbow(id, bbow)
+    }
+
+    /// Add the document `id`, already reduced to `bbow`, to
+    /// this corpus, replacing any document with the same
+    /// `id`. The BBOW should follow the rules of the
+    /// corpus, or its words may not be found.
+    pub fn insert_bbow(mut self, id: Id, bbow: Bbow<'a>) -> Self {
+        self.remove_document(&id);
+        let length = bbow.count();
+        self.lengths.insert(id.clone(), length);
+        self.total_length += length;
+        for (word, &count) in &bbow.counts {
+            entry(&mut self.postings, word).insert(id.clone(), count);
+        }
+        self.documents.insert(id, bbow);
+        self
+    }
+
+    /// Remove the document `id` from this corpus, returning
+    /// its BBOW if it was present.
+    pub fn remove_document(&mut self, id: &Id) -> Option<Bbow<'a>> {
+        let bbow = self.documents.remove(id)?;
+        self.total_length -= self.lengths.remove(id).unwrap_or(0);
+        for word in bbow.counts.keys() {
+            if let Some(documents) = self.postings.get_mut(word.as_ref()) {
+                documents.remove(id);
+                if documents.is_empty() {
+                    self.postings.remove(word.as_ref());
+                }
+            }
+        }
+        Some(bbow)
+    }
+
+    /// The BBOW of the document `id`, if present.
+    pub fn document(&self, id: &Id) -> Option<&Bbow<'a>> {
+        self.documents.get(id)
+    }
+
+    /// The documents of this corpus, in order of their ids.
+    pub fn documents(&self) -> impl Iterator<Item = (&Id, &Bbow<'a>)> {
+        self.documents.iter()
+    }
+
+    /// Report the number of words in the document `id`, or
+    /// 0 if it is not in this corpus.
+    pub fn document_length(&self, id: &Id) -> usize {
+        *self.lengths.get(id).unwrap_or(&0usize)
+    }
+
+    /// The mean number of words in the documents of this
+    /// corpus, or 0 if it is empty.
+    pub fn average_length(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.total_length as f64 / self.len() as f64
+        }
+    }
+
+    /// The documents containing `word`, in order of their
+    /// ids, each with the number of occurrences of the word
+    /// in it. The word is normalized by the rules of the
+    /// corpus.
+    pub fn postings(&self, word: &str) -> impl Iterator<Item = (&Id, usize)> {
+        let word = self.analyzer().normalize(word);
+        self.postings
+            .get(word.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|(id, &count)| (id, count))
+    }
+
+    /// Report the number of documents containing `word`.
+    pub fn document_frequency(&self, word: &str) -> usize {
+        let word = self.analyzer().normalize(word);
+        self.postings.get(word.as_ref()).map_or(0, BTreeMap::len)
+    }
+
+    /// The distinct words of this corpus, in order.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.postings.keys().map(|word| word.as_ref())
+    }
+
+    /// Count the number of documents in this corpus.
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Is this corpus empty?
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacing_a_document_should_update_postings() {
+        let mut corpus = Corpus::new()
+            .insert_document("a", "one two")
+            .insert_document("b", "two")
+            .insert_document("a", "three");
+
+        assert_eq!(0, corpus.document_frequency("one"));
+        assert_eq!(1, corpus.document_frequency("two"));
+        assert_eq!(vec!["three", "two"], corpus.words().collect::<Vec<_>>());
+
+        corpus.remove_document(&"b");
+        assert_eq!(vec!["three"], corpus.words().collect::<Vec<_>>());
+        assert_eq!(1, corpus.len());
+        assert_eq!(1, corpus.document_length(&"a"));
+        assert_eq!(1.0, corpus.average_length());
+    }
+
+    #[test]
+    fn posting_keys_should_stay_borrowed() {
+        let corpus = Corpus::new().insert_bbow(0, Bbow::new().extend_from_text("word"));
+
+        let (key, _) = corpus.postings.first_key_value().unwrap();
+        assert!(matches!(key, Cow::Borrowed(_)));
+    }
+}
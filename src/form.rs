@@ -0,0 +1,92 @@
+//! Unicode normalization of words.
+
+use std::borrow::Cow;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::{is_nfc, is_nfkc, UnicodeNormalization};
+
+/// A Unicode normalization form applied to words, so that
+/// canonically (or compatibly) equivalent spellings share a
+/// key.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Bbow, NormalizationForm};
+/// let bbow = Bbow::new()
+///     .with_normalization(NormalizationForm::Nfc)
+///     .extend_from_text("unt\u{ef}l unti\u{308}l");
+/// assert_eq!(1, bbow.len());
+/// assert_eq!(2, bbow.match_count("unt\u{ef}l"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationForm {
+    /// Canonical composition (NFC).
+    Nfc,
+    /// Compatibility composition (NFKC): in addition to
+    /// canonical composition, compatibility characters such
+    /// as ligatures and full-width letters are replaced by
+    /// their ordinary equivalents.
+    Nfkc,
+}
+
+impl NormalizationForm {
+    /// Normalize `word` to this form. Words that are
+    /// already normalized are borrowed rather than copied.
+    pub fn apply<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        match self {
+            NormalizationForm::Nfc if !is_nfc(word) => Cow::from(word.nfc().collect::<String>()),
+            NormalizationForm::Nfkc if !is_nfkc(word) => Cow::from(word.nfkc().collect::<String>()),
+            _ => Cow::from(word),
+        }
+    }
+}
+
+/// Remove diacritics from `word` by decomposing it and
+/// dropping the combining marks, so that "café" becomes
+/// "cafe". The result is recomposed to NFC. Words without
+/// diacritics are borrowed rather than copied.
+pub(crate) fn fold_diacritics(word: &str) -> Cow<'_, str> {
+    if word.is_ascii() {
+        return Cow::from(word);
+    }
+
+    let folded: String = word
+        .nfd()
+        .filter(|&c| !is_combining_mark(c))
+        .nfc()
+        .collect();
+    if folded == word {
+        Cow::from(word)
+    } else {
+        Cow::from(folded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_words_should_be_borrowed() {
+        assert!(matches!(
+            NormalizationForm::Nfc.apply("unt\u{ef}l"),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!("unt\u{ef}l", NormalizationForm::Nfc.apply("unti\u{308}l"));
+    }
+
+    #[test]
+    fn nfkc_should_replace_compatibility_characters() {
+        assert_eq!("find", NormalizationForm::Nfkc.apply("\u{fb01}nd"));
+        assert_eq!("\u{fb01}nd", NormalizationForm::Nfc.apply("\u{fb01}nd"));
+    }
+
+    #[test]
+    fn fold_diacritics_should_drop_combining_marks() {
+        assert_eq!("cafe", fold_diacritics("caf\u{e9}"));
+        assert_eq!("cafe", fold_diacritics("cafe\u{301}"));
+        assert_eq!("\u{f8}l", fold_diacritics("\u{f8}l"));
+        assert!(matches!(fold_diacritics("東京"), Cow::Borrowed(_)));
+    }
+}
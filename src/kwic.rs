@@ -0,0 +1,226 @@
+//! Keyword-in-context (KWIC) concordances of the words of a
+//! BBOW with positions recorded.
+
+use std::fmt;
+
+use crate::Bbow;
+
+/// How much context surrounds each occurrence in a
+/// concordance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// Up to this many whitespace-separated words on each
+    /// side, with their punctuation.
+    Words(usize),
+    /// Up to this many characters on each side.
+    Chars(usize),
+}
+
+/// One line of a concordance: an occurrence of the keyword
+/// with its context, as slices of the original text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KwicLine<'t> {
+    /// The index of the text containing the occurrence.
+    pub text: usize,
+    /// The context preceding the occurrence.
+    pub left: &'t str,
+    /// The occurrence itself, as it appears in the text.
+    pub keyword: &'t str,
+    /// The context following the occurrence.
+    pub right: &'t str,
+}
+
+/// A keyword-in-context concordance: a sequence of
+/// [`KwicLine`]s. Its display aligns the keywords of the
+/// lines in a column, one line per occurrence.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Bbow, Context};
+/// let bbow = Bbow::new()
+///     .with_positions()
+///     .extend_from_text("The cat sat on the mat.\nA big cat ran.");
+/// let concordance = bbow.concordance("cat", Context::Words(1));
+/// assert_eq!("The cat sat\nbig cat ran.\n", concordance.to_string());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Concordance<'t>(Vec<KwicLine<'t>>);
+
+impl<'t> Concordance<'t> {
+    /// The lines of this concordance, in the order their
+    /// occurrences were found.
+    pub fn lines(&self) -> &[KwicLine<'t>] {
+        &self.0
+    }
+
+    /// The number of lines in this concordance.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is this concordance empty?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'t> IntoIterator for Concordance<'t> {
+    type Item = KwicLine<'t>;
+    type IntoIter = std::vec::IntoIter<KwicLine<'t>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Replace line breaks and other whitespace with spaces,
+/// so that each line of a concordance stays on one line.
+fn flatten(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect()
+}
+
+impl fmt::Display for Concordance<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .0
+            .iter()
+            .map(|line| line.left.chars().count())
+            .max()
+            .unwrap_or(0);
+        for line in &self.0 {
+            writeln!(
+                f,
+                "{:>width$}{}{}",
+                flatten(line.left),
+                flatten(line.keyword),
+                flatten(line.right),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The suffix of `before` holding up to `n` whole words,
+/// together with any part of a word attached to what
+/// follows.
+fn left_words(before: &str, n: usize) -> &str {
+    let mut start = before.len();
+    let mut chars = before.char_indices().rev().peekable();
+    let mut take_word = |chars: &mut std::iter::Peekable<_>| {
+        while let Some(&(i, c)) = chars.peek() {
+            if char::is_whitespace(c) {
+                break;
+            }
+            start = i;
+            chars.next();
+        }
+    };
+
+    take_word(&mut chars);
+    for _ in 0..n {
+        while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        take_word(&mut chars);
+    }
+    &before[start..]
+}
+
+/// The prefix of `after` holding up to `n` whole words,
+/// together with any part of a word attached to what
+/// precedes.
+fn right_words(after: &str, n: usize) -> &str {
+    let mut end = 0;
+    let mut chars = after.char_indices().peekable();
+    let mut take_word = |chars: &mut std::iter::Peekable<_>| {
+        while let Some(&(i, c)) = chars.peek() {
+            if char::is_whitespace(c) {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+    };
+
+    take_word(&mut chars);
+    for _ in 0..n {
+        while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        take_word(&mut chars);
+    }
+    &after[..end]
+}
+
+impl<'a> Bbow<'a> {
+    /// Build a keyword-in-context concordance of the
+    /// recorded occurrences of `keyword`, with the given
+    /// amount of `context`. The keyword is normalized as by
+    /// [`Bbow::match_count`]. Only occurrences in texts
+    /// added after [`Bbow::with_positions`] are found.
+    pub fn concordance(&self, keyword: &str, context: Context) -> Concordance<'_> {
+        let lines = self
+            .occurrences(keyword)
+            .filter_map(|occurrence| {
+                let text = self.text(occurrence.text)?;
+                let before = &text[..occurrence.span.start];
+                let after = &text[occurrence.span.end..];
+                let (left, right) = match context {
+                    Context::Words(n) => (left_words(before, n), right_words(after, n)),
+                    Context::Chars(n) => {
+                        let start = match n {
+                            0 => before.len(),
+                            n => before.char_indices().rev().nth(n - 1).map_or(0, |(i, _)| i),
+                        };
+                        let end = after.char_indices().nth(n).map_or(after.len(), |(i, _)| i);
+                        (&before[start..], &after[..end])
+                    }
+                };
+                Some(KwicLine {
+                    text: occurrence.text,
+                    left,
+                    keyword: &text[occurrence.span.clone()],
+                    right,
+                })
+            })
+            .collect();
+        Concordance(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_context_should_keep_attached_punctuation() {
+        let before = "one two, (";
+        let after = "), three four";
+
+        assert_eq!("two, (", left_words(before, 1));
+        assert_eq!("one two, (", left_words(before, 5));
+        assert_eq!("(", left_words(before, 0));
+        assert_eq!("), three", right_words(after, 1));
+    }
+
+    #[test]
+    fn char_context_should_count_chars() {
+        let bbow = Bbow::new().with_positions().extend_from_text("ééé cat ééé");
+
+        let concordance = bbow.concordance("cat", Context::Chars(2));
+        assert_eq!(
+            vec![KwicLine {
+                text: 0,
+                left: "é ",
+                keyword: "cat",
+                right: " é"
+            }],
+            concordance.lines()
+        );
+    }
+}
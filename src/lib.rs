@@ -23,24 +23,144 @@
 //! contains the sequence of words `"It"`, `"over"`,
 //! `"untïl"`, `"it"`, `"over"`.
 //!
+//! The splitting of text into words can be customized by
+//! supplying a [`Tokenizer`] to
+//! [`Bbow::extend_with_tokenizer`]. The
+//! [`UnicodeWordTokenizer`] follows the Unicode word
+//! boundary rules rather than splitting on whitespace.
+//!
+//! The handling of internal punctuation is controlled by a
+//! [`PunctuationPolicy`]: for example, "ain't" can be kept
+//! whole and "well-known" split into two words.
+//!
 //! Words in the bag containing uppercase letters will be
-//! represented by their lowercase equivalent.
+//! represented by their lowercase equivalent. Other case
+//! normalizations, such as full Unicode case folding, can
+//! be selected with a [`CaseMode`]. Words may also be
+//! brought to a Unicode [`NormalizationForm`], so that
+//! equivalent spellings are counted together. Combining
+//! marks, such as the diaeresis of a decomposed "ï", are
+//! considered part of the letter they follow. For
+//! search-style use, diacritics can be removed altogether.
+//!
+//! Words can also be reduced to their stems by a
+//! [`Stemmer`], so that "running" and "runs" are counted
+//! together. The surface forms counted under each stem
+//! remain available.
+//!
+//! Common words such as "the" can be left out of the bag
+//! by supplying a set of [`Stopwords`].
+//!
+//! Rather than single words, a BBOW can count the n-grams
+//! of a text: sequences of `n` consecutive words, such as
+//! "new york". See [`Bbow::with_ngrams`]. Character
+//! n-grams of the words of a text are counted by
+//! [`CharNgrams`], and pairs of words occurring near each
+//! other by [`Cooccurrences`].
+//!
+//! A BBOW can also record the position of every
+//! occurrence of its words: see [`Bbow::with_positions`].
+//! From these, keyword-in-context [`Concordance`]s can be
+//! built, phrases and nearby words searched for, and the
+//! line and column of each occurrence found.
+//!
+//! Many documents, each reduced to a BBOW, can be collected
+//! into a [`Corpus`] that finds the documents containing
+//! each word. Their words can be weighted by [`TfIdf`],
+//! and the documents ranked against a query by [`Bm25`].
+//! Documents can also be selected by a boolean [`Query`].
+//!
+//! Two BBOWs can be compared by measures such as
+//! [`Bbow::cosine`] and [`Bbow::jaccard`], and combined by
+//! multiset operations such as [`Bbow::sum`], also written
+//! `+`. Bags built from different texts can be merged with
+//! [`Bbow::merge`], and [`Bbow::into_owned`] frees a bag
+//! from its texts.
+//!
+//! Text too large to hold in memory can be streamed in
+//! with [`Bbow::extend_from_reader`], and
+//! bytes that may not be valid UTF-8 parsed with
+//! [`Bbow::extend_from_bytes`]. Large files can be
+//! memory-mapped as a [`MappedFile`], so that words are
+//! borrowed from the mapping.
+
+mod algebra;
+mod analyzer;
+mod bm25;
+mod bytes;
+mod case;
+mod chargram;
+mod cooccur;
+mod corpus;
+mod form;
+mod kwic;
+mod mmap;
+mod ngram;
+mod owned;
+mod phrase;
+mod position;
+mod punctuation;
+mod query;
+mod reader;
+mod similarity;
+mod stem;
+mod stopwords;
+mod tfidf;
+mod tokenize;
+
+pub use analyzer::Analyzer;
+pub use bm25::Bm25;
+pub use bytes::Utf8Policy;
+pub use case::{CaseMode, Locale};
+pub use chargram::CharNgrams;
+pub use cooccur::{Cooccurrences, Weighting};
+pub use corpus::Corpus;
+pub use form::NormalizationForm;
+pub use kwic::{Concordance, Context, KwicLine};
+pub use mmap::MappedFile;
+pub use phrase::Match;
+pub use position::{LineColumn, Occurrence};
+pub use punctuation::{InternalPunctuation, PunctuationPolicy};
+pub use query::{ParseError, Query};
+pub use reader::{InvalidUtf8, ReadError};
+pub use stem::Stemmer;
+pub use stopwords::{Language, Stopwords};
+pub use tfidf::{InverseDocumentFrequency, TermFrequency, TfIdf};
+pub use tokenize::{Tokenizer, UnicodeWordTokenizer, WhitespaceTokenizer};
 
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
+
+use analyzer::Token;
+
+/// Find the value for `key` in `map`, inserting a default
+/// value first if needed. The key is only cloned when it is
+/// inserted, so a borrowed key stays borrowed.
+#[allow(clippy::ptr_arg)]
+pub(crate) fn entry<'m, 'a, V: Default>(
+    map: &'m mut BTreeMap<Cow<'a, str>, V>,
+    key: &Cow<'a, str>,
+) -> &'m mut V {
+    if !map.contains_key(key.as_ref()) {
+        map.insert(key.clone(), V::default());
+    }
+    map.get_mut(key.as_ref()).unwrap()
+}
 
 /// Each key in this struct's map is a word in some
 /// in-memory text document. The corresponding value is the
 /// count of occurrences.
 #[derive(Debug, Default, Clone)]
-pub struct Bbow<'a>(BTreeMap<Cow<'a, str>, usize>);
-
-fn is_word(word: &str) -> bool {
-    !word.is_empty() && word.chars().all(|c| c.is_alphabetic())
-}
-
-fn has_uppercase(word: &str) -> bool {
-    word.chars().any(char::is_uppercase)
+pub struct Bbow<'a> {
+    counts: BTreeMap<Cow<'a, str>, usize>,
+    surfaces: BTreeMap<Cow<'a, str>, BTreeMap<Cow<'a, str>, usize>>,
+    analyzer: Analyzer,
+    /// The length of the n-grams counted: 0 is taken as 1.
+    ngram: usize,
+    /// The texts added with positions recorded, if
+    /// recording.
+    texts: Option<Vec<Cow<'a, str>>>,
+    positions: BTreeMap<Cow<'a, str>, Vec<Occurrence>>,
 }
 
 impl<'a> Bbow<'a> {
@@ -49,6 +169,127 @@ impl<'a> Bbow<'a> {
         Self::default()
     }
 
+    /// Use the rules of the given `analyzer` for words
+    /// subsequently added to or looked up in this BBOW.
+    pub fn with_analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    /// Use the given policy for internal punctuation in
+    /// words subsequently added to this BBOW.
+    pub fn with_punctuation(mut self, punctuation: PunctuationPolicy) -> Self {
+        self.analyzer = self.analyzer.with_punctuation(punctuation);
+        self
+    }
+
+    /// Use the given case normalization for words
+    /// subsequently added to or looked up in this BBOW.
+    pub fn with_case(mut self, case: CaseMode) -> Self {
+        self.analyzer = self.analyzer.with_case(case);
+        self
+    }
+
+    /// Normalize words subsequently added to or looked up
+    /// in this BBOW to the given Unicode normalization
+    /// `form`.
+    pub fn with_normalization(mut self, form: NormalizationForm) -> Self {
+        self.analyzer = self.analyzer.with_normalization(form);
+        self
+    }
+
+    /// Choose whether diacritics are removed from words
+    /// subsequently added to or looked up in this BBOW.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let bbow = Bbow::new()
+    ///     .with_diacritic_folding(true)
+    ///     .extend_from_text("café cafe CAFÉ");
+    /// assert_eq!(1, bbow.len());
+    /// assert_eq!(3, bbow.match_count("Café"));
+    /// ```
+    pub fn with_diacritic_folding(mut self, fold: bool) -> Self {
+        self.analyzer = self.analyzer.with_diacritic_folding(fold);
+        self
+    }
+
+    /// Reduce words subsequently added to or looked up in
+    /// this BBOW to their stems with the given `stemmer`.
+    /// The surface forms counted under each stem are
+    /// recorded: see [`Bbow::surface_forms`].
+    pub fn with_stemmer(mut self, stemmer: Stemmer) -> Self {
+        self.analyzer = self.analyzer.with_stemmer(stemmer);
+        self
+    }
+
+    /// Leave the given `stopwords` out of the words
+    /// subsequently added to this BBOW. To remove
+    /// stopwords already counted, see
+    /// [`Bbow::remove_stopwords`].
+    pub fn with_stopwords(mut self, stopwords: Stopwords) -> Self {
+        self.analyzer = self.analyzer.with_stopwords(stopwords);
+        self
+    }
+
+    /// Count the `n`-grams of texts subsequently added to
+    /// this BBOW, rather than single words. The key of an
+    /// n-gram is its words separated by single spaces: it
+    /// is borrowed from the text when the text contains
+    /// exactly those words so separated. An n-gram never
+    /// spans a stopword left out by [`Bbow::with_stopwords`],
+    /// or a token that is not a valid word.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let bbow = Bbow::new()
+    ///     .with_ngrams(2)
+    ///     .extend_from_text("New York, new York!");
+    /// assert_eq!(3, bbow.count());
+    /// assert_eq!(2, bbow.match_sequence(&["new", "york"]));
+    /// assert_eq!(1, bbow.match_sequence(&["York", "new"]));
+    /// ```
+    pub fn with_ngrams(mut self, n: usize) -> Self {
+        assert!(n > 0, "n-grams must contain at least one word");
+        self.ngram = n;
+        self
+    }
+
+    /// Record the position of every occurrence of the words
+    /// of texts subsequently added to this BBOW. The texts
+    /// themselves are retained, so that occurrences can be
+    /// found in them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let bbow = Bbow::new()
+    ///     .with_positions()
+    ///     .extend_from_text("The cat sat. The end.");
+    /// let spans: Vec<_> = bbow.occurrences("the").map(|o| o.span.clone()).collect();
+    /// assert_eq!(vec![0..3, 13..16], spans);
+    /// let ordinals: Vec<_> = bbow.occurrences("the").map(|o| o.ordinal).collect();
+    /// assert_eq!(vec![0, 3], ordinals);
+    /// ```
+    pub fn with_positions(mut self) -> Self {
+        self.texts.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// The rules used by this BBOW to find words.
+    pub fn analyzer(&self) -> &Analyzer {
+        &self.analyzer
+    }
+
     /// Parse the `target` text and add the sequence of
     /// valid words contained in it to this BBOW.
     ///
@@ -64,39 +305,107 @@ impl<'a> Bbow<'a> {
     /// assert_eq!(2, bbow.len());
     /// assert_eq!(1, bbow.match_count("hello"));
     /// ```
-    pub fn extend_from_text(mut self, target: &'a str) -> Self {
-        target
-            .split_whitespace()
-            .filter_map(|word| {
-                let trimmed = word.trim_matches(|c: char| !c.is_alphabetic());
+    pub fn extend_from_text(self, target: &'a str) -> Self {
+        self.extend_with_tokenizer(target, &WhitespaceTokenizer)
+    }
 
-                if is_word(trimmed) {
-                    Some(trimmed)
-                } else {
-                    None
-                }
-            })
-            .for_each(|word| {
-                let word = if has_uppercase(word) {
-                    Cow::from(word.to_lowercase())
-                } else {
-                    Cow::from(word)
-                };
-
-                self.0
-                    .entry(word)
-                    .and_modify(|count| *count += 1)
-                    .or_insert(1);
-            });
+    /// Split the `target` text into tokens using the given
+    /// `tokenizer`, and add the sequence of valid words
+    /// among them to this BBOW. Tokens that are not valid
+    /// words are ignored.
+    ///
+    /// Like [`Bbow::extend_from_text`], this is a builder
+    /// method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tokenizer produces a token that is not
+    /// a slice of `target`, as required by [`Tokenizer`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bbow, Tokenizer};
+    /// struct Commas;
+    ///
+    /// impl Tokenizer for Commas {
+    ///     fn tokens<'t>(&self, text: &'t str) -> impl Iterator<Item = &'t str> {
+    ///         text.split(',').map(str::trim)
+    ///     }
+    /// }
+    ///
+    /// let bbow = Bbow::new().extend_with_tokenizer("apple,Pear, apple", &Commas);
+    /// assert_eq!(2, bbow.match_count("apple"));
+    /// assert_eq!(1, bbow.match_count("pear"));
+    /// ```
+    pub fn extend_with_tokenizer<T: Tokenizer>(self, target: &'a str, tokenizer: &T) -> Self {
+        self.extend_continuing(target, tokenizer, &mut VecDeque::new())
+    }
+
+    /// Add the words of `target` as by
+    /// [`Bbow::extend_with_tokenizer`], continuing the
+    /// n-grams of the preceding text whose last words are
+    /// held in `window`: see [`ngram::ngrams`]. Positions
+    /// would not be consistent across the two texts, so
+    /// `window` must be empty if positions are recorded.
+    pub(crate) fn extend_continuing<T: Tokenizer>(
+        mut self,
+        target: &'a str,
+        tokenizer: &T,
+        window: &mut VecDeque<Token<'a>>,
+    ) -> Self {
+        let text = self.texts.as_mut().map(|texts| {
+            texts.push(Cow::from(target));
+            texts.len() - 1
+        });
+        let analyzer = std::mem::take(&mut self.analyzer);
+        let tokens = analyzer.analyze(tokenizer, target);
+        for token in ngram::ngrams(target, self.ngram.max(1), window, tokens) {
+            self.insert(token, text);
+        }
+        self.analyzer = analyzer;
 
         self
     }
 
+    /// Count one occurrence of the word (or n-gram) found
+    /// as `token`, recording its position in the given
+    /// `text` if any.
+    fn insert(&mut self, token: Token<'a>, text: Option<usize>) {
+        if let Some(text) = text {
+            entry(&mut self.positions, token.key()).push(Occurrence {
+                text,
+                span: token.span.clone(),
+                ordinal: token.ordinal,
+            });
+        }
+
+        let word = match token.stem {
+            Some(stem) => {
+                *self
+                    .surfaces
+                    .entry(stem.clone())
+                    .or_default()
+                    .entry(token.surface)
+                    .or_insert(0) += 1;
+                stem
+            }
+            None => token.surface,
+        };
+
+        self.counts
+            .entry(word)
+            .and_modify(|count| *count += 1)
+            .or_insert(1);
+    }
+
     /// Report the number of occurrences of the given
     /// `keyword` that are indexed by this BBOW. The keyword
-    /// should be lowercase and not contain punctuation, as
-    /// per the rules of BBOW: otherwise the keyword will
-    /// not match and 0 will be returned.
+    /// is normalized by the same rules as the words of the
+    /// BBOW, so its case does not matter; but it should
+    /// not contain punctuation, as per the rules of BBOW:
+    /// otherwise the keyword will not match and 0 will be
+    /// returned.
     ///
     /// # Examples:
     ///
@@ -105,13 +414,149 @@ impl<'a> Bbow<'a> {
     /// let bbow = Bbow::new()
     ///     .extend_from_text("b b b-banana b");
     /// assert_eq!(3, bbow.match_count("b"));
+    /// assert_eq!(3, bbow.match_count("B"));
     /// ```
     pub fn match_count(&self, keyword: &str) -> usize {
-        *self.0.get(keyword).unwrap_or(&0usize)
+        let keyword = self.analyzer.normalize(keyword);
+        *self.counts.get(keyword.as_ref()).unwrap_or(&0usize)
+    }
+
+    /// Report the number of occurrences of the n-gram made
+    /// of the given sequence of `words` that are indexed by
+    /// this BBOW. Each word is normalized by the rules of
+    /// the BBOW, as by [`Bbow::match_count`].
+    pub fn match_sequence(&self, words: &[&str]) -> usize {
+        let key = words
+            .iter()
+            .map(|word| self.analyzer.normalize(word))
+            .collect::<Vec<_>>()
+            .join(ngram::SEPARATOR);
+        *self.counts.get(key.as_str()).unwrap_or(&0usize)
+    }
+
+    /// Report the surface forms counted under the stem of
+    /// the given `keyword`, each with its number of
+    /// occurrences. Surface forms are only recorded when
+    /// this BBOW has a stemmer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bbow, Stemmer};
+    /// let bbow = Bbow::new()
+    ///     .with_stemmer(Stemmer::Porter2)
+    ///     .extend_from_text("Running runs; running run.");
+    /// assert_eq!(4, bbow.match_count("run"));
+    /// let forms: Vec<_> = bbow.surface_forms("runner's").collect();
+    /// assert!(forms.is_empty());
+    /// let forms: Vec<_> = bbow.surface_forms("run").collect();
+    /// assert_eq!(vec![("run", 1), ("running", 2), ("runs", 1)], forms);
+    /// ```
+    pub fn surface_forms(&self, keyword: &str) -> impl Iterator<Item = (&str, usize)> + '_ {
+        let keyword = self.analyzer.normalize(keyword);
+        self.surfaces
+            .get(keyword.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|(surface, &count)| (surface.as_ref(), count))
+    }
+
+    /// Remove the given `stopwords` from the words already
+    /// counted in this BBOW, reporting the number of
+    /// occurrences removed. Stopwords are normalized by the
+    /// rules of this BBOW before being removed; when
+    /// stemming, only the matching surface forms are
+    /// removed from each stem.
+    ///
+    /// Only single words are removed: on a BBOW counting
+    /// n-grams of two or more words, nothing is removed and
+    /// 0 is returned. To keep stopwords out of n-grams,
+    /// leave them out while counting, with
+    /// [`Bbow::with_stopwords`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bbow, Language, Stopwords};
+    /// let mut bbow = Bbow::new().extend_from_text("The cat and the hat.");
+    /// let removed = bbow.remove_stopwords(&Stopwords::builtin(Language::English));
+    /// assert_eq!(3, removed);
+    /// assert_eq!(2, bbow.count());
+    /// ```
+    pub fn remove_stopwords(&mut self, stopwords: &Stopwords) -> usize {
+        let mut removed = 0;
+        for stopword in stopwords.iter() {
+            let surface = self.analyzer.surface(stopword);
+            if self.analyzer.stemmer().is_none() {
+                removed += self.counts.remove(surface.as_ref()).unwrap_or(0);
+                self.positions.remove(surface.as_ref());
+                continue;
+            }
+
+            let word = self.analyzer.stem(surface.clone());
+            if let (Some(texts), Some(occurrences)) =
+                (&self.texts, self.positions.get_mut(word.as_ref()))
+            {
+                occurrences.retain(|occurrence| {
+                    let found = &texts[occurrence.text][occurrence.span.clone()];
+                    self.analyzer.surface(found) != surface
+                });
+                if occurrences.is_empty() {
+                    self.positions.remove(word.as_ref());
+                }
+            }
+            let Some(forms) = self.surfaces.get_mut(word.as_ref()) else {
+                continue;
+            };
+            let Some(count) = forms.remove(surface.as_ref()) else {
+                continue;
+            };
+            if forms.is_empty() {
+                self.surfaces.remove(word.as_ref());
+                self.counts.remove(word.as_ref());
+            } else if let Some(total) = self.counts.get_mut(word.as_ref()) {
+                *total -= count;
+            }
+            removed += count;
+        }
+        removed
+    }
+
+    /// The recorded occurrences of the given `keyword`, in
+    /// the order they were found. The keyword is
+    /// normalized as by [`Bbow::match_count`]. Occurrences
+    /// are only recorded for texts added after
+    /// [`Bbow::with_positions`].
+    pub fn occurrences(&self, keyword: &str) -> impl Iterator<Item = &Occurrence> {
+        let keyword = self.analyzer.normalize(keyword);
+        self.positions.get(keyword.as_ref()).into_iter().flatten()
+    }
+
+    /// The text with the given `index` among those added to
+    /// this BBOW with positions recorded, if any.
+    pub fn text(&self, index: usize) -> Option<&str> {
+        self.texts.as_ref()?.get(index).map(|text| text.as_ref())
     }
 
     pub fn words(&'a self) -> impl Iterator<Item = &'a str> {
-        self.0.keys().map(|w| w.as_ref())
+        self.counts.keys().map(|w| w.as_ref())
+    }
+
+    /// The distinct words in this BBOW, in order, each with
+    /// its number of occurrences.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let bbow = Bbow::new().extend_from_text("b a b");
+    /// let counts: Vec<_> = bbow.iter().collect();
+    /// assert_eq!(vec![("a", 1), ("b", 2)], counts);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.counts
+            .iter()
+            .map(|(word, &count)| (word.as_ref(), count))
     }
 
     /// Count the overall number of words contained in this BBOW:
@@ -126,7 +571,7 @@ impl<'a> Bbow<'a> {
     /// assert_eq!(3, bbow.count());
     /// ```
     pub fn count(&self) -> usize {
-        self.0.values().sum()
+        self.counts.values().sum()
     }
 
     /// Count the number of unique words contained in this BBOW,
@@ -141,12 +586,12 @@ impl<'a> Bbow<'a> {
     /// assert_eq!(2, bbow.len());
     /// ```
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.counts.len()
     }
 
     /// Is this BBOW empty?
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.counts.is_empty()
     }
 }
 
@@ -195,19 +640,257 @@ mod tests {
 
         let my_bbow = Bbow::new().extend_from_text(test_str);
 
-        let (one_key, _) = my_bbow.0.get_key_value("one").unwrap();
-        let (two_key, _) = my_bbow.0.get_key_value("two").unwrap();
+        let (one_key, _) = my_bbow.counts.get_key_value("one").unwrap();
+        let (two_key, _) = my_bbow.counts.get_key_value("two").unwrap();
 
         assert!(matches!(one_key, Cow::Borrowed(_)));
         assert!(matches!(two_key, Cow::Owned(_)))
     }
 
+    #[test]
+    fn custom_tokenizer_keys_should_be_borrowed() {
+        struct Dashes;
+
+        impl Tokenizer for Dashes {
+            fn tokens<'t>(&self, text: &'t str) -> impl Iterator<Item = &'t str> {
+                text.split('-')
+            }
+        }
+
+        let test_str = "one-Two-one";
+
+        let my_bbow = Bbow::new().extend_with_tokenizer(test_str, &Dashes);
+
+        let (one_key, _) = my_bbow.counts.get_key_value("one").unwrap();
+        assert!(matches!(one_key, Cow::Borrowed(_)));
+        assert_eq!(2, my_bbow.match_count("one"));
+        assert_eq!(1, my_bbow.match_count("two"));
+    }
+
+    #[test]
+    #[should_panic(expected = "token is not a slice of its text")]
+    fn tokens_not_from_the_text_should_panic() {
+        struct Synonyms;
+
+        impl Tokenizer for Synonyms {
+            fn tokens<'t>(&self, text: &'t str) -> impl Iterator<Item = &'t str> {
+                text.split_whitespace()
+                    .map(|word| if word == "automobile" { "car" } else { word })
+            }
+        }
+
+        Bbow::new().extend_with_tokenizer("red automobile", &Synonyms);
+    }
+
+    #[test]
+    fn unicode_words_should_split_on_punctuation() {
+        let test_str = "hello,World;hello";
+
+        let my_bbow = Bbow::new().extend_with_tokenizer(test_str, &UnicodeWordTokenizer);
+
+        let (hello_key, _) = my_bbow.counts.get_key_value("hello").unwrap();
+        assert!(matches!(hello_key, Cow::Borrowed(_)));
+        assert_eq!(2, my_bbow.match_count("hello"));
+        assert_eq!(1, my_bbow.match_count("world"));
+    }
+
+    #[test]
+    fn kept_punctuation_should_stay_borrowed() {
+        let test_str = "don't U.S.";
+        let policy = PunctuationPolicy::new()
+            .apostrophe(InternalPunctuation::Keep)
+            .period(InternalPunctuation::Keep);
+
+        let my_bbow = Bbow::new()
+            .with_punctuation(policy)
+            .extend_from_text(test_str);
+
+        let (dont_key, _) = my_bbow.counts.get_key_value("don't").unwrap();
+        assert!(matches!(dont_key, Cow::Borrowed(_)));
+        assert_eq!(1, my_bbow.match_count("u.s"));
+    }
+
+    #[test]
+    fn normalized_keys_should_merge_and_stay_borrowed() {
+        let test_str = "unt\u{ef}l UNTI\u{308}L";
+
+        let my_bbow = Bbow::new()
+            .with_normalization(NormalizationForm::Nfc)
+            .extend_from_text(test_str);
+
+        assert_eq!(1, my_bbow.len());
+        assert_eq!(2, my_bbow.match_count("unti\u{308}l"));
+        let (key, _) = my_bbow.counts.get_key_value("unt\u{ef}l").unwrap();
+        assert!(matches!(key, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn stopwords_should_match_normalized_words() {
+        let test_str = "Über den Fluss und über die Brücke";
+        let stopwords = Stopwords::builtin(Language::German);
+
+        let my_bbow = Bbow::new()
+            .with_stopwords(stopwords)
+            .with_diacritic_folding(true)
+            .extend_from_text(test_str);
+
+        assert_eq!(2, my_bbow.len());
+        assert_eq!(1, my_bbow.match_count("fluss"));
+        assert_eq!(1, my_bbow.match_count("brucke"));
+    }
+
+    #[test]
+    fn ngrams_should_not_span_stopwords() {
+        let test_str = "a bag of words and big words";
+        let stopwords = Stopwords::builtin(Language::English);
+
+        let my_bbow = Bbow::new()
+            .with_stopwords(stopwords)
+            .with_ngrams(2)
+            .extend_from_text(test_str);
+
+        let ngrams: Vec<_> = my_bbow.iter().collect();
+        assert_eq!(vec![("big words", 1)], ngrams);
+    }
+
+    #[test]
+    fn ngrams_should_not_span_dropped_tokens() {
+        let test_str = "It ain't over, room 42 service";
+
+        let my_bbow = Bbow::new().with_ngrams(2).extend_from_text(test_str);
+
+        let ngrams: Vec<_> = my_bbow.iter().collect();
+        assert_eq!(vec![("over room", 1)], ngrams);
+    }
+
+    #[test]
+    fn remove_stopwords_should_keep_other_surface_forms() {
+        let test_str = "being beings be";
+        let stopwords: Stopwords = ["being"].into_iter().collect();
+
+        let mut my_bbow = Bbow::new()
+            .with_stemmer(Stemmer::Porter2)
+            .extend_from_text(test_str);
+
+        assert_eq!(3, my_bbow.match_count("being"));
+        assert_eq!(1, my_bbow.remove_stopwords(&stopwords));
+        assert_eq!(2, my_bbow.match_count("being"));
+        let forms: Vec<_> = my_bbow.surface_forms("be").collect();
+        assert_eq!(vec![("be", 1), ("beings", 1)], forms);
+    }
+
+    #[test]
+    fn remove_stopwords_should_leave_ngrams_alone() {
+        let test_str = "the cat and the hat";
+
+        let mut my_bbow = Bbow::new().with_ngrams(2).extend_from_text(test_str);
+
+        let removed = my_bbow.remove_stopwords(&Stopwords::builtin(Language::English));
+        assert_eq!(0, removed);
+        assert_eq!(4, my_bbow.count());
+    }
+
+    #[test]
+    fn ngram_keys_should_borrow_contiguous_lowercase_spans() {
+        let test_str = "the big bag, of Words";
+
+        let my_bbow = Bbow::new().with_ngrams(2).extend_from_text(test_str);
+
+        assert_eq!(4, my_bbow.len());
+        let (big_bag, _) = my_bbow.counts.get_key_value("big bag").unwrap();
+        assert!(matches!(big_bag, Cow::Borrowed(_)));
+        let (bag_of, _) = my_bbow.counts.get_key_value("bag of").unwrap();
+        assert!(matches!(bag_of, Cow::Owned(_)));
+        assert_eq!(1, my_bbow.match_sequence(&["of", "words"]));
+    }
+
+    #[test]
+    fn stemmed_ngrams_should_record_surface_forms() {
+        let test_str = "running fast runs fast";
+
+        let my_bbow = Bbow::new()
+            .with_ngrams(2)
+            .with_stemmer(Stemmer::Porter2)
+            .extend_from_text(test_str);
+
+        assert_eq!(2, my_bbow.match_sequence(&["run", "fast"]));
+        let forms: Vec<_> = my_bbow.surfaces["run fast"]
+            .keys()
+            .map(|k| k.as_ref())
+            .collect();
+        assert_eq!(vec!["running fast", "runs fast"], forms);
+    }
+
+    #[test]
+    fn positions_should_span_texts_and_ngrams() {
+        let my_bbow = Bbow::new()
+            .with_positions()
+            .with_ngrams(2)
+            .extend_from_text("big bag")
+            .extend_from_text("a big, bag");
+
+        let occurrences: Vec<_> = my_bbow.occurrences("big bag").cloned().collect();
+        assert_eq!(
+            vec![
+                Occurrence {
+                    text: 0,
+                    span: 0..7,
+                    ordinal: 0
+                },
+                Occurrence {
+                    text: 1,
+                    span: 2..10,
+                    ordinal: 1
+                },
+            ],
+            occurrences
+        );
+        assert_eq!(Some("a big, bag"), my_bbow.text(1));
+    }
+
+    #[test]
+    fn remove_stopwords_should_remove_their_positions() {
+        let stopwords: Stopwords = ["being"].into_iter().collect();
+
+        let mut my_bbow = Bbow::new()
+            .with_positions()
+            .with_stemmer(Stemmer::Porter2)
+            .extend_from_text("being be");
+        my_bbow.remove_stopwords(&stopwords);
+
+        let spans: Vec<_> = my_bbow.occurrences("be").map(|o| o.span.clone()).collect();
+        assert_eq!(vec![6..8], spans);
+    }
+
     #[test]
     fn match_count_should_return_0_with_bad_key() {
         let test_str = "one";
 
         let my_bbow = Bbow::new().extend_from_text(test_str);
 
-        assert_eq!(0, my_bbow.match_count("One"))
+        assert_eq!(0, my_bbow.match_count("one!"))
+    }
+
+    #[test]
+    fn match_count_should_normalize_keyword() {
+        let test_str = "one";
+
+        let my_bbow = Bbow::new().extend_from_text(test_str);
+
+        assert_eq!(1, my_bbow.match_count("One"))
+    }
+
+    #[test]
+    fn preserved_case_should_keep_separate_keys() {
+        let test_str = "one One";
+
+        let my_bbow = Bbow::new()
+            .with_case(CaseMode::Preserve)
+            .extend_from_text(test_str);
+
+        assert_eq!(2, my_bbow.len());
+        assert_eq!(1, my_bbow.match_count("One"));
+        let (upper_key, _) = my_bbow.counts.get_key_value("One").unwrap();
+        assert!(matches!(upper_key, Cow::Borrowed(_)));
     }
 }
@@ -0,0 +1,107 @@
+//! Ingestion of text from memory-mapped files.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{Bbow, InvalidUtf8, Utf8Policy};
+
+/// A file mapped into memory, so that its contents can be
+/// read without copying them to the heap. A BBOW built from
+/// the mapping borrows its words from the mapping, and so
+/// cannot outlive it.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bbow::{Bbow, MappedFile, Utf8Policy};
+/// // Safety: the corpus is not modified while mapped.
+/// let file = unsafe { MappedFile::open("corpus.txt") }.unwrap();
+/// let mut bbow = Bbow::new();
+/// bbow.extend_from_mapped(&file, Utf8Policy::Replace).unwrap();
+/// println!("{} distinct words", bbow.len());
+/// ```
+#[derive(Debug)]
+pub struct MappedFile {
+    map: Mmap,
+}
+
+impl MappedFile {
+    /// Map the file at `path` into memory, read-only.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this
+    /// or any other process, while it is mapped: the
+    /// contents of the mapping, including any `&str`
+    /// borrowed from it, would change underneath the
+    /// program, which is undefined behavior.
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: upheld by the caller.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Self { map })
+    }
+
+    /// The contents of the file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// The contents of the file as text.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the contents are not valid UTF-8.
+    pub fn as_str(&self) -> Result<&str, InvalidUtf8> {
+        std::str::from_utf8(&self.map).map_err(|error| InvalidUtf8::new(error.valid_up_to()))
+    }
+}
+
+impl<'a> Bbow<'a> {
+    /// Parse the contents of the mapped `file` as by
+    /// [`Bbow::extend_from_bytes`], and add the sequence of
+    /// valid words contained in it to this BBOW. Words are
+    /// borrowed from the mapping as they would be from the
+    /// bytes.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the contents are not valid UTF-8 and the
+    /// policy is [`Utf8Policy::Strict`]. This BBOW is then
+    /// left unchanged.
+    pub fn extend_from_mapped(
+        &mut self,
+        file: &'a MappedFile,
+        policy: Utf8Policy,
+    ) -> Result<(), InvalidUtf8> {
+        self.extend_from_bytes(file.as_bytes(), policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn words_should_borrow_from_the_mapping() {
+        let path = std::env::temp_dir().join(format!("bbow-mmap-{}.txt", std::process::id()));
+        std::fs::write(&path, "Mapped words, mapped\n").unwrap();
+        // Safety: the file is private to this test.
+        let file = unsafe { MappedFile::open(&path) }.unwrap();
+
+        let mut bbow = Bbow::new();
+        bbow.extend_from_mapped(&file, Utf8Policy::Strict).unwrap();
+        assert_eq!(2, bbow.match_count("mapped"));
+        let (key, _) = bbow.counts.get_key_value("words").unwrap();
+        assert!(matches!(key, Cow::Borrowed(_)));
+        assert_eq!(Ok("Mapped words, mapped\n"), file.as_str());
+
+        drop(bbow);
+        drop(file);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
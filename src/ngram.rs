@@ -0,0 +1,118 @@
+//! Joining of consecutive words into n-grams.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::analyzer::Token;
+
+/// The separator between the words of an n-gram key.
+pub(crate) const SEPARATOR: &str = " ";
+
+/// Join a sequence of `words` found at the given spans of
+/// `text` into an n-gram. When every word is exactly as it
+/// appears in the text, and the words are separated in the
+/// text by single spaces, the n-gram is borrowed from the
+/// text rather than copied.
+pub(crate) fn join<'a, 'w>(
+    text: &'a str,
+    words: impl Iterator<Item = (&'w Range<usize>, &'w Cow<'a, str>)> + Clone,
+) -> Cow<'a, str>
+where
+    'a: 'w,
+{
+    let mut span: Option<Range<usize>> = None;
+    let contiguous = words.clone().all(|(word_span, word)| {
+        let verbatim = matches!(word, Cow::Borrowed(w) if w.len() == word_span.len());
+        let adjacent = match &span {
+            Some(span) => text.get(span.end..word_span.start) == Some(SEPARATOR),
+            None => true,
+        };
+        span = Some(span.as_ref().map_or(word_span.start, |span| span.start)..word_span.end);
+        verbatim && adjacent
+    });
+
+    match span {
+        Some(span) if contiguous => Cow::from(&text[span]),
+        _ => Cow::from(
+            words
+                .map(|(_, word)| word.as_ref())
+                .collect::<Vec<_>>()
+                .join(SEPARATOR),
+        ),
+    }
+}
+
+/// Turn a sequence of `tokens` found in `text` into the
+/// sequence of their `n`-grams. Each n-gram spans the words
+/// it contains, which are consecutive: a `None` between two
+/// words, for a stopword or a token that is not a valid
+/// word, separates them.
+///
+/// The n-grams continue from the words of a preceding text
+/// held in `window`, which is left holding the last words
+/// of `text`, to begin the n-grams of the text that
+/// follows. Words carried over in this way are owned, so
+/// the n-grams containing them are copied.
+pub(crate) fn ngrams<'a, 'w>(
+    text: &'a str,
+    n: usize,
+    window: &'w mut VecDeque<Token<'a>>,
+    tokens: impl Iterator<Item = Option<Token<'a>>> + 'w,
+) -> impl Iterator<Item = Token<'a>> + 'w {
+    tokens.filter_map(move |token| {
+        let Some(token) = token else {
+            window.clear();
+            return None;
+        };
+        window.push_back(token);
+        if window.len() < n {
+            return None;
+        }
+        if n == 1 {
+            return window.pop_front();
+        }
+
+        let gram = Token {
+            span: window[0].span.start..window[n - 1].span.end,
+            ordinal: window[0].ordinal,
+            surface: join(
+                text,
+                window.iter().map(|token| (&token.span, &token.surface)),
+            ),
+            stem: window[0]
+                .stem
+                .is_some()
+                .then(|| join(text, window.iter().map(|token| (&token.span, token.key())))),
+        };
+        window.pop_front();
+        Some(gram)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contiguous_words_should_be_borrowed() {
+        let text = "in new york city";
+        let words = [
+            (3..6, Cow::from(&text[3..6])),
+            (7..11, Cow::from(&text[7..11])),
+        ];
+
+        let joined = join(text, words.iter().map(|(span, word)| (span, word)));
+        assert!(matches!(joined, Cow::Borrowed("new york")));
+    }
+
+    #[test]
+    fn separated_or_changed_words_should_be_owned() {
+        let text = "new  York";
+        let words = [(0..3, Cow::from(&text[0..3])), (5..9, Cow::from("york"))];
+
+        let joined = join(text, words.iter().map(|(span, word)| (span, word)));
+        assert!(matches!(joined, Cow::Owned(_)));
+        assert_eq!("new york", joined);
+    }
+}
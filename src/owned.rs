@@ -0,0 +1,123 @@
+//! Conversion of BBOWs to owned form, and merging of BBOWs
+//! borrowing from unrelated texts.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::Bbow;
+
+/// Make an owned copy of every key of `map`, converting its
+/// values by `f`.
+fn owned_keys<V, W>(
+    map: BTreeMap<Cow<'_, str>, V>,
+    f: impl Fn(V) -> W,
+) -> BTreeMap<Cow<'static, str>, W> {
+    map.into_iter()
+        .map(|(key, value)| (Cow::Owned(key.into_owned()), f(value)))
+        .collect()
+}
+
+impl<'a> Bbow<'a> {
+    /// Convert this BBOW into one that owns all its keys
+    /// and texts, so that it no longer borrows from the
+    /// texts it was built from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let bbow = {
+    ///     let text = String::from("Hello, hello world");
+    ///     Bbow::new().extend_from_text(&text).into_owned()
+    /// };
+    /// assert_eq!(2, bbow.match_count("hello"));
+    /// ```
+    pub fn into_owned(self) -> Bbow<'static> {
+        Bbow {
+            counts: owned_keys(self.counts, |count| count),
+            surfaces: owned_keys(self.surfaces, |forms| owned_keys(forms, |count| count)),
+            analyzer: self.analyzer,
+            ngram: self.ngram,
+            texts: self.texts.map(|texts| {
+                texts
+                    .into_iter()
+                    .map(|text| Cow::Owned(text.into_owned()))
+                    .collect()
+            }),
+            positions: owned_keys(self.positions, |occurrences| occurrences),
+        }
+    }
+
+    /// Add the counts, surface forms and positions of
+    /// `other` to this BBOW, as by [`Bbow::sum`], whatever
+    /// texts `other` borrows from. Only the keys this BBOW
+    /// does not already have are copied, and the texts of
+    /// `other` only if both BBOWs record positions.
+    ///
+    /// Like [`Bbow::extend_from_text`], this is a builder
+    /// method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let text = String::from("one two");
+    /// let mut bbow = Bbow::new().extend_from_text(&text);
+    /// for line in ["two three", "three"] {
+    ///     let buffer = line.to_string();
+    ///     bbow = bbow.merge(&Bbow::new().extend_from_text(&buffer));
+    /// }
+    /// assert_eq!(2, bbow.match_count("three"));
+    /// ```
+    pub fn merge(mut self, other: &Bbow<'_>) -> Self {
+        self.add_bag(other, |key| Cow::Owned(key.to_string()));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_should_copy_only_new_keys() {
+        let text = String::from("one two");
+        let bbow = Bbow::new().extend_from_text(&text);
+        let other = String::from("two three");
+        let bbow = bbow.merge(&Bbow::new().extend_from_text(&other));
+        drop(other);
+
+        let (two, _) = bbow.counts.get_key_value("two").unwrap();
+        assert!(matches!(two, Cow::Borrowed(_)));
+        let (three, _) = bbow.counts.get_key_value("three").unwrap();
+        assert!(matches!(three, Cow::Owned(_)));
+        assert_eq!(2, bbow.match_count("two"));
+    }
+
+    #[test]
+    fn merge_should_not_start_recording_positions() {
+        let other = String::from("two");
+        let bbow = Bbow::new()
+            .extend_from_text("one two")
+            .merge(&Bbow::new().with_positions().extend_from_text(&other));
+
+        assert_eq!(0, bbow.occurrences("two").count());
+        assert_eq!(None, bbow.text(0));
+    }
+
+    #[test]
+    fn into_owned_should_keep_positions_and_surfaces() {
+        let bbow = {
+            let text = String::from("Running runs");
+            Bbow::new()
+                .with_stemmer(crate::Stemmer::Porter2)
+                .with_positions()
+                .extend_from_text(&text)
+                .into_owned()
+        };
+
+        assert_eq!(Some("Running runs"), bbow.text(0));
+        assert_eq!(2, bbow.occurrences("run").count());
+        assert_eq!(2, bbow.surface_forms("run").count());
+    }
+}
@@ -0,0 +1,201 @@
+//! Phrase and proximity search over the positions recorded
+//! by a BBOW.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::{Bbow, WhitespaceTokenizer};
+
+/// A match of a phrase or proximity search in a text added
+/// to a BBOW with positions recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match<'t> {
+    /// The index of the text containing the match: see
+    /// [`Bbow::text`].
+    pub text: usize,
+    /// The byte range of the match in its text, from the
+    /// start of its first word to the end of its last.
+    pub span: Range<usize>,
+    /// The matched part of the text.
+    pub matched: &'t str,
+}
+
+impl<'a> Bbow<'a> {
+    /// Find the recorded occurrences of the words of
+    /// `phrase` in sequence, in the order they were found.
+    /// The phrase is parsed by the rules of this BBOW, and
+    /// its words must be as far apart in the text, by
+    /// their [ordinals](crate::Occurrence::ordinal), as they
+    /// are in the phrase. Only texts added after
+    /// [`Bbow::with_positions`] are searched, and only
+    /// BBOWs of single words can be searched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bbow, Language, Stopwords};
+    /// let text = "A big bag of words, a big bag, of words.";
+    /// let bbow = Bbow::new()
+    ///     .with_stopwords(Stopwords::builtin(Language::English))
+    ///     .with_positions()
+    ///     .extend_from_text(text);
+    /// let matches = bbow.phrase("big bag of words");
+    /// assert_eq!(2, matches.len());
+    /// assert_eq!("big bag of words", matches[0].matched);
+    /// assert_eq!(&text[22..39], matches[1].matched);
+    /// ```
+    pub fn phrase(&self, phrase: &str) -> Vec<Match<'_>> {
+        let words: Vec<(usize, Cow<str>)> = self
+            .analyzer
+            .analyze(&WhitespaceTokenizer, phrase)
+            .flatten()
+            .map(|token| (token.ordinal, token.into_key()))
+            .collect();
+        let Some(((origin, first), rest)) = words.split_first() else {
+            return Vec::new();
+        };
+        let ends: Option<Vec<BTreeMap<(usize, usize), usize>>> = rest
+            .iter()
+            .map(|(_, word)| {
+                let occurrences = self.positions.get(word.as_ref())?;
+                Some(
+                    occurrences
+                        .iter()
+                        .map(|o| ((o.text, o.ordinal), o.span.end))
+                        .collect(),
+                )
+            })
+            .collect();
+        let Some(ends) = ends else {
+            return Vec::new();
+        };
+
+        self.positions
+            .get(first.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|start| {
+                let mut end = start.span.end;
+                for ((ordinal, _), word) in rest.iter().zip(&ends) {
+                    end = *word.get(&(start.text, start.ordinal + ordinal - origin))?;
+                }
+                self.found(start.text, start.span.start..end)
+            })
+            .collect()
+    }
+
+    /// Find the recorded occurrences of `a` and `b` at most
+    /// `distance` words apart, by their
+    /// [ordinals](crate::Occurrence::ordinal), in either
+    /// order: with a distance of 1 the words must be
+    /// adjacent. Both words are normalized as by
+    /// [`Bbow::match_count`]. Matches are in the order the
+    /// occurrences of `a` were found. As with
+    /// [`Bbow::phrase`], positions must be recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let bbow = Bbow::new()
+    ///     .with_positions()
+    ///     .extend_from_text("Words in a bag. A bag of many words.");
+    /// let matched: Vec<_> = bbow.near("bag", "words", 3).iter().map(|m| m.matched).collect();
+    /// assert_eq!(vec!["Words in a bag", "bag of many words"], matched);
+    /// assert!(bbow.near("bag", "words", 2).is_empty());
+    /// ```
+    pub fn near(&self, a: &str, b: &str, distance: usize) -> Vec<Match<'_>> {
+        let a = self.analyzer.normalize(a);
+        let b = self.analyzer.normalize(b);
+        let same = a == b;
+        let others: BTreeMap<(usize, usize), &Range<usize>> = self
+            .positions
+            .get(b.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|o| ((o.text, o.ordinal), &o.span))
+            .collect();
+
+        let mut matches = Vec::new();
+        for first in self.positions.get(a.as_ref()).into_iter().flatten() {
+            let low = if same {
+                first.ordinal + 1
+            } else {
+                first.ordinal.saturating_sub(distance)
+            };
+            let high = first.ordinal.saturating_add(distance);
+            for (_, span) in others.range((first.text, low)..=(first.text, high)) {
+                let span = first.span.start.min(span.start)..first.span.end.max(span.end);
+                matches.extend(self.found(first.text, span));
+            }
+        }
+        matches
+    }
+
+    /// The match of the given `span` of the text with the
+    /// given index.
+    fn found(&self, text: usize, span: Range<usize>) -> Option<Match<'_>> {
+        let matched = self.text(text)?.get(span.clone())?;
+        Some(Match {
+            text,
+            span,
+            matched,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phrases_should_not_span_texts() {
+        let bbow = Bbow::new()
+            .with_positions()
+            .extend_from_text("one two")
+            .extend_from_text("three one")
+            .extend_from_text("two one two");
+
+        let matches = bbow.phrase("One, two!");
+        assert_eq!(
+            vec![(0, 0..7), (2, 4..11)],
+            matches
+                .into_iter()
+                .map(|m| (m.text, m.span))
+                .collect::<Vec<_>>()
+        );
+        assert!(bbow.phrase("one three").is_empty());
+        assert!(bbow.phrase("").is_empty());
+    }
+
+    #[test]
+    fn dropped_tokens_should_keep_words_apart() {
+        let bbow = Bbow::new()
+            .with_positions()
+            .extend_from_text("room 42 service, room service");
+
+        let matched: Vec<_> = bbow
+            .phrase("room service")
+            .into_iter()
+            .map(|m| m.matched)
+            .collect();
+        assert_eq!(vec!["room service"], matched);
+        let matched: Vec<_> = bbow
+            .phrase("room 7 service")
+            .into_iter()
+            .map(|m| m.matched)
+            .collect();
+        assert_eq!(vec!["room 42 service"], matched);
+        assert_eq!(2, bbow.near("room", "service", 1).len());
+        assert_eq!(3, bbow.near("room", "service", 2).len());
+    }
+
+    #[test]
+    fn near_should_pair_a_word_with_itself_once() {
+        let bbow = Bbow::new().with_positions().extend_from_text("a a x a");
+
+        let spans: Vec<_> = bbow.near("a", "a", 2).into_iter().map(|m| m.span).collect();
+        assert_eq!(vec![0..3, 2..7], spans);
+    }
+}
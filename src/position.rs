@@ -0,0 +1,108 @@
+//! Positions of the occurrences of words in their texts.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::Bbow;
+
+/// One occurrence of a word (or n-gram) in a text added to
+/// a BBOW with positions recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Occurrence {
+    /// The index of the text among those added to the
+    /// BBOW: see [`Bbow::text`](crate::Bbow::text).
+    pub text: usize,
+    /// The byte range of the occurrence in its text.
+    pub span: Range<usize>,
+    /// The index of the occurrence in the sequence of words
+    /// found in its text. Stopwords, and tokens that are
+    /// not valid words, are counted too, so that only
+    /// words next to each other in the text have
+    /// consecutive ordinals.
+    pub ordinal: usize,
+}
+
+/// A 1-based line and column in a text. Lines are
+/// separated by `\n`; columns are counted in characters,
+/// not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineColumn {
+    /// The line, counting from 1.
+    pub line: usize,
+    /// The column, counting from 1.
+    pub column: usize,
+}
+
+impl fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// The byte offsets at which the lines of `text` start.
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+impl<'a> Bbow<'a> {
+    /// The recorded occurrences of the given `keyword`, in
+    /// the order they were found, each with the line and
+    /// column at which it starts in its text. The keyword
+    /// is normalized as by [`Bbow::match_count`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let bbow = Bbow::new()
+    ///     .with_positions()
+    ///     .extend_from_text("fn main() {\n    // déjà vu: main\n}");
+    /// let places: Vec<_> = bbow
+    ///     .line_columns("main")
+    ///     .map(|(_, place)| place.to_string())
+    ///     .collect();
+    /// assert_eq!(vec!["1:4", "2:17"], places);
+    /// ```
+    pub fn line_columns(&self, keyword: &str) -> impl Iterator<Item = (&Occurrence, LineColumn)> {
+        let mut lines: Option<(usize, Vec<usize>)> = None;
+        self.occurrences(keyword).filter_map(move |occurrence| {
+            let text = self.text(occurrence.text)?;
+            if !matches!(&lines, Some((index, _)) if *index == occurrence.text) {
+                lines = Some((occurrence.text, line_starts(text)));
+            }
+            let (_, starts) = lines.as_ref()?;
+
+            let line = starts.partition_point(|&start| start <= occurrence.span.start);
+            let column = text[starts[line - 1]..occurrence.span.start]
+                .chars()
+                .count()
+                + 1;
+            Some((occurrence, LineColumn { line, column }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_columns_should_restart_for_each_text() {
+        let bbow = Bbow::new()
+            .with_positions()
+            .extend_from_text("a\nb a")
+            .extend_from_text("ä a");
+
+        let places: Vec<_> = bbow.line_columns("a").map(|(_, place)| place).collect();
+        assert_eq!(
+            vec![
+                LineColumn { line: 1, column: 1 },
+                LineColumn { line: 2, column: 3 },
+                LineColumn { line: 1, column: 3 },
+            ],
+            places
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Policies for punctuation found inside a token, such as
+//! the apostrophe in "ain't" or the hyphen in
+//! "well-known".
+
+use std::collections::BTreeMap;
+
+use crate::tokenize::is_letter;
+
+/// What to do with a token containing a given internal
+/// punctuation character.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InternalPunctuation {
+    /// Keep the token whole, punctuation included.
+    Keep,
+    /// Split the token into parts at the punctuation,
+    /// counting each part as a separate word.
+    Split,
+    /// Drop the token entirely.
+    #[default]
+    Drop,
+}
+
+/// Decides, character by character, how punctuation
+/// inside a token is handled. Leading and trailing
+/// punctuation is always trimmed from the token (and
+/// from each part of a split token).
+///
+/// The default policy drops every token containing
+/// internal punctuation.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Bbow, InternalPunctuation, PunctuationPolicy};
+/// let policy = PunctuationPolicy::new()
+///     .apostrophe(InternalPunctuation::Keep)
+///     .hyphen(InternalPunctuation::Split);
+/// let bbow = Bbow::new()
+///     .with_punctuation(policy)
+///     .extend_from_text("Ain't it well-known?");
+/// assert_eq!(1, bbow.match_count("ain't"));
+/// assert_eq!(1, bbow.match_count("well"));
+/// assert_eq!(1, bbow.match_count("known"));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PunctuationPolicy(BTreeMap<char, InternalPunctuation>);
+
+impl PunctuationPolicy {
+    /// Make a new policy that drops every token containing
+    /// internal punctuation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the `action` taken for internal occurrences of
+    /// the character `c`.
+    pub fn character(mut self, c: char, action: InternalPunctuation) -> Self {
+        self.0.insert(c, action);
+        self
+    }
+
+    /// Set the `action` taken for internal apostrophes,
+    /// either ASCII (`'`) or typographic (`’`).
+    pub fn apostrophe(self, action: InternalPunctuation) -> Self {
+        self.character('\'', action).character('\u{2019}', action)
+    }
+
+    /// Set the `action` taken for internal hyphens, either
+    /// ASCII (`-`) or Unicode (`‐`, `‑`).
+    pub fn hyphen(self, action: InternalPunctuation) -> Self {
+        self.character('-', action)
+            .character('\u{2010}', action)
+            .character('\u{2011}', action)
+    }
+
+    /// Set the `action` taken for internal periods, as in
+    /// "U.S.A".
+    pub fn period(self, action: InternalPunctuation) -> Self {
+        self.character('.', action)
+    }
+
+    /// Report the action taken for internal occurrences of
+    /// the character `c`.
+    pub fn action(&self, c: char) -> InternalPunctuation {
+        self.0.get(&c).copied().unwrap_or_default()
+    }
+
+    /// Apply this policy to `token`, producing the sequence
+    /// of words it contains. Every word is a slice of the
+    /// token.
+    pub(crate) fn words<'s, 't>(&'s self, token: &'t str) -> impl Iterator<Item = &'t str> + 's
+    where
+        't: 's,
+    {
+        let token = trim(token);
+        let keep = !token.is_empty()
+            && token
+                .chars()
+                .all(|c| is_letter(c) || self.action(c) != InternalPunctuation::Drop);
+
+        keep.then(|| token.split(|c| self.action(c) == InternalPunctuation::Split))
+            .into_iter()
+            .flatten()
+            .map(trim)
+            .filter(|part| !part.is_empty())
+    }
+}
+
+fn trim(token: &str) -> &str {
+    token.trim_matches(|c| !is_letter(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_should_drop_internal_punctuation() {
+        let policy = PunctuationPolicy::new();
+
+        assert_eq!(vec!["over"], policy.words("over").collect::<Vec<_>>());
+        assert!(policy.words("ain't").next().is_none());
+        assert!(policy.words("well-known").next().is_none());
+    }
+
+    #[test]
+    fn split_parts_should_be_trimmed() {
+        let policy = PunctuationPolicy::new()
+            .hyphen(InternalPunctuation::Split)
+            .apostrophe(InternalPunctuation::Keep);
+
+        let words: Vec<_> = policy.words("rock-'n'-roll").collect();
+        assert_eq!(vec!["rock", "n", "roll"], words);
+    }
+
+    #[test]
+    fn mixed_actions_should_drop_when_any_char_drops() {
+        let policy = PunctuationPolicy::new().hyphen(InternalPunctuation::Split);
+
+        assert!(policy.words("well-known's").next().is_none());
+    }
+}
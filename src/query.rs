@@ -0,0 +1,527 @@
+//! A boolean query language over the documents of a
+//! corpus.
+//!
+//! A query is made of terms combined by the operators
+//! `AND`, `OR` and `NOT`, which must be written in
+//! uppercase, and grouped by parentheses. `NOT` binds most
+//! tightly, then `AND`, then `OR`. Terms written next to
+//! each other are joined by `AND`, so `rust NOT unsafe`
+//! means `rust AND NOT unsafe`.
+//!
+//! A phrase in double quotes, such as `"big bag of words"`,
+//! matches documents containing its words in sequence, and
+//! `bag NEAR/3 words` matches documents containing the two
+//! terms at most 3 words apart; the operands of `NEAR/`
+//! must be single terms. Both need the positions of words
+//! to be recorded: see
+//! [`Bbow::with_positions`](crate::Bbow::with_positions)
+//! and [`Corpus::with_template`].
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Corpus, WhitespaceTokenizer};
+
+/// A parsed boolean query.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::Query;
+/// let query: Query = "rust AND (borrow OR lifetime) NOT unsafe".parse().unwrap();
+/// assert_eq!(
+///     Query::and(
+///         Query::and(
+///             Query::term("rust"),
+///             Query::or(Query::term("borrow"), Query::term("lifetime")),
+///         ),
+///         !Query::term("unsafe"),
+///     ),
+///     query,
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// Documents containing the term.
+    Term(String),
+    /// Documents matching both queries.
+    And(Box<Query>, Box<Query>),
+    /// Documents matching either query.
+    Or(Box<Query>, Box<Query>),
+    /// Documents not matching the query.
+    Not(Box<Query>),
+    /// Documents containing the words of the phrase in
+    /// sequence.
+    Phrase(String),
+    /// Documents containing the two terms at most the given
+    /// number of words apart.
+    Near(String, String, usize),
+}
+
+impl Query {
+    /// Make a query for documents containing `term`.
+    pub fn term(term: &str) -> Self {
+        Query::Term(term.to_string())
+    }
+
+    /// Make a query for documents matching both `a` and
+    /// `b`.
+    pub fn and(a: Query, b: Query) -> Self {
+        Query::And(Box::new(a), Box::new(b))
+    }
+
+    /// Make a query for documents matching either `a` or
+    /// `b`.
+    pub fn or(a: Query, b: Query) -> Self {
+        Query::Or(Box::new(a), Box::new(b))
+    }
+
+    /// Parse the `text` of a query.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: lex(text)?,
+            next: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let query = parser.or()?;
+        match parser.tokens.get(parser.next) {
+            None => Ok(query),
+            Some(&(Lexeme::Close, offset)) => Err(ParseError::Unopened { offset }),
+            Some(&(lexeme, offset)) => Err(ParseError::Unexpected {
+                token: lexeme.to_string(),
+                offset,
+            }),
+        }
+    }
+}
+
+impl std::ops::Not for Query {
+    type Output = Query;
+
+    /// Make a query for documents not matching this one.
+    fn not(self) -> Query {
+        Query::Not(Box::new(self))
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Query::parse(text)
+    }
+}
+
+/// The ways in which the text of a [`Query`] can be
+/// malformed. Offsets are in bytes from the start of the
+/// text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The query contains nothing.
+    Empty,
+    /// The query ends where a term was expected.
+    UnexpectedEnd,
+    /// An operator or `)` appears where a term was
+    /// expected.
+    Unexpected {
+        /// The token found.
+        token: String,
+        /// Where the token starts.
+        offset: usize,
+    },
+    /// A `(` is never closed.
+    Unclosed {
+        /// Where the `(` is.
+        offset: usize,
+    },
+    /// A `)` closes no `(`.
+    Unopened {
+        /// Where the `)` is.
+        offset: usize,
+    },
+    /// A `"` is never closed.
+    UnclosedQuote {
+        /// Where the `"` is.
+        offset: usize,
+    },
+    /// A `NEAR/` is not followed by a distance.
+    BadDistance {
+        /// Where the `NEAR/` starts.
+        offset: usize,
+    },
+    /// An operand of `NEAR/` is not a single term, such as
+    /// a phrase or a group in parentheses.
+    NearOperand {
+        /// Where the operand starts.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty query"),
+            ParseError::UnexpectedEnd => write!(f, "query ends where a term was expected"),
+            ParseError::Unexpected { token, offset } => {
+                write!(f, "expected a term at byte {offset}, found `{token}`")
+            }
+            ParseError::Unclosed { offset } => write!(f, "unclosed `(` at byte {offset}"),
+            ParseError::Unopened { offset } => write!(f, "unmatched `)` at byte {offset}"),
+            ParseError::UnclosedQuote { offset } => write!(f, "unclosed `\"` at byte {offset}"),
+            ParseError::BadDistance { offset } => {
+                write!(f, "expected a distance after `NEAR/` at byte {offset}")
+            }
+            ParseError::NearOperand { offset } => {
+                write!(f, "`NEAR/` operands must be single terms, at byte {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lexeme<'q> {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Near(usize),
+    Term(&'q str),
+    Phrase(&'q str),
+}
+
+impl fmt::Display for Lexeme<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Lexeme::Open => write!(f, "("),
+            Lexeme::Close => write!(f, ")"),
+            Lexeme::And => write!(f, "AND"),
+            Lexeme::Or => write!(f, "OR"),
+            Lexeme::Not => write!(f, "NOT"),
+            Lexeme::Near(distance) => write!(f, "NEAR/{distance}"),
+            Lexeme::Term(term) => write!(f, "{term}"),
+            Lexeme::Phrase(phrase) => write!(f, "\"{phrase}\""),
+        }
+    }
+}
+
+/// Split the text of a query into lexemes, each with its
+/// offset.
+fn lex(text: &str) -> Result<Vec<(Lexeme<'_>, usize)>, ParseError> {
+    let word = |start: usize, end: usize| {
+        let lexeme = match &text[start..end] {
+            "AND" => Lexeme::And,
+            "OR" => Lexeme::Or,
+            "NOT" => Lexeme::Not,
+            word => match word.strip_prefix("NEAR/") {
+                Some(distance) => match distance.parse() {
+                    Ok(distance) => Lexeme::Near(distance),
+                    Err(_) => return Err(ParseError::BadDistance { offset: start }),
+                },
+                None => Lexeme::Term(word),
+            },
+        };
+        Ok((lexeme, start))
+    };
+
+    let mut lexemes = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        let lexeme = match c {
+            '(' => Lexeme::Open,
+            ')' => Lexeme::Close,
+            '"' => {
+                let (end, _) = chars
+                    .find(|&(_, c)| c == '"')
+                    .ok_or(ParseError::UnclosedQuote { offset: i })?;
+                Lexeme::Phrase(&text[i + 1..end])
+            }
+            c if c.is_whitespace() => {
+                if let Some(start) = start.take() {
+                    lexemes.push(word(start, i)?);
+                }
+                continue;
+            }
+            _ => {
+                start.get_or_insert(i);
+                continue;
+            }
+        };
+        if let Some(start) = start.take() {
+            lexemes.push(word(start, i)?);
+        }
+        lexemes.push((lexeme, i));
+    }
+    if let Some(start) = start {
+        lexemes.push(word(start, text.len())?);
+    }
+    Ok(lexemes)
+}
+
+/// A recursive descent parser over the lexemes of a query.
+struct Parser<'q> {
+    tokens: Vec<(Lexeme<'q>, usize)>,
+    next: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<Lexeme<'_>> {
+        self.tokens.get(self.next).map(|&(lexeme, _)| lexeme)
+    }
+
+    fn or(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.and()?;
+        while self.peek() == Some(Lexeme::Or) {
+            self.next += 1;
+            query = Query::or(query, self.and()?);
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(Lexeme::And) => self.next += 1,
+                Some(Lexeme::Open | Lexeme::Not | Lexeme::Term(_) | Lexeme::Phrase(_)) => (),
+                _ => return Ok(query),
+            }
+            query = Query::and(query, self.unary()?);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Query, ParseError> {
+        let Some(&(lexeme, offset)) = self.tokens.get(self.next) else {
+            return Err(ParseError::UnexpectedEnd);
+        };
+        self.next += 1;
+        let query = match lexeme {
+            Lexeme::Not => Ok(!self.unary()?),
+            Lexeme::Term(term) => match self.peek() {
+                Some(Lexeme::Near(distance)) => {
+                    self.next += 1;
+                    match self.tokens.get(self.next) {
+                        Some(&(Lexeme::Term(other), _)) => {
+                            self.next += 1;
+                            Ok(Query::Near(term.to_string(), other.to_string(), distance))
+                        }
+                        Some(&(Lexeme::Phrase(_) | Lexeme::Open | Lexeme::Not, offset)) => {
+                            Err(ParseError::NearOperand { offset })
+                        }
+                        Some(&(lexeme, offset)) => Err(ParseError::Unexpected {
+                            token: lexeme.to_string(),
+                            offset,
+                        }),
+                        None => Err(ParseError::UnexpectedEnd),
+                    }
+                }
+                _ => Ok(Query::term(term)),
+            },
+            Lexeme::Phrase(phrase) => Ok(Query::Phrase(phrase.to_string())),
+            Lexeme::Open => {
+                let query = self.or()?;
+                if self.peek() == Some(Lexeme::Close) {
+                    self.next += 1;
+                    Ok(query)
+                } else {
+                    Err(ParseError::Unclosed { offset })
+                }
+            }
+            Lexeme::Close | Lexeme::And | Lexeme::Or | Lexeme::Near(_) => {
+                Err(ParseError::Unexpected {
+                    token: lexeme.to_string(),
+                    offset,
+                })
+            }
+        }?;
+        if let Some(Lexeme::Near(_)) = self.peek() {
+            return Err(ParseError::NearOperand { offset });
+        }
+        Ok(query)
+    }
+}
+
+impl<'a, Id: Ord + Clone> Corpus<'a, Id> {
+    /// Parse the boolean `query` and find the documents
+    /// matching it, as by [`Corpus::evaluate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Corpus;
+    /// let corpus = Corpus::new()
+    ///     .insert_document(1, "Rust borrow checking")
+    ///     .insert_document(2, "Rust lifetimes, unsafe code")
+    ///     .insert_document(3, "C++ lifetime rules");
+    /// let ids = corpus.query("rust AND (borrow OR lifetimes)").unwrap();
+    /// assert_eq!(vec![&1, &2], ids.into_iter().collect::<Vec<_>>());
+    /// assert!(corpus.query("rust AND (borrow").is_err());
+    /// ```
+    pub fn query(&self, query: &str) -> Result<BTreeSet<&Id>, ParseError> {
+        Ok(self.evaluate(&query.parse()?))
+    }
+
+    /// Find the documents matching the `query`. Its terms
+    /// are normalized by the rules of the corpus; a term
+    /// that is a stopword is found in no document, though
+    /// stopwords in phrases are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Bbow, Corpus, Query};
+    /// let corpus = Corpus::new()
+    ///     .with_template(Bbow::new().with_positions())
+    ///     .insert_document("a", "a big bag of words")
+    ///     .insert_document("b", "words in a big bag");
+    /// let query = Query::parse(r#""big bag" AND bag NEAR/2 words"#).unwrap();
+    /// assert_eq!(vec![&"a"], corpus.evaluate(&query).into_iter().collect::<Vec<_>>());
+    /// ```
+    pub fn evaluate(&self, query: &Query) -> BTreeSet<&Id> {
+        match query {
+            Query::Term(term) => {
+                let term = self.analyzer().normalize(term);
+                self.postings
+                    .get(term.as_ref())
+                    .into_iter()
+                    .flat_map(|documents| documents.keys())
+                    .collect()
+            }
+            Query::And(a, b) => match b.as_ref() {
+                Query::Not(b) => &self.evaluate(a) - &self.evaluate(b),
+                b => &self.evaluate(a) & &self.evaluate(b),
+            },
+            Query::Or(a, b) => &self.evaluate(a) | &self.evaluate(b),
+            Query::Phrase(phrase) => {
+                let mut words = self.analyzer().words(&WhitespaceTokenizer, phrase);
+                let Some(first) = words.next() else {
+                    return BTreeSet::new();
+                };
+                self.postings
+                    .get(first.as_ref())
+                    .into_iter()
+                    .flat_map(|documents| documents.keys())
+                    .filter(|id| {
+                        let bbow = self.document(id);
+                        bbow.is_some_and(|bbow| !bbow.phrase(phrase).is_empty())
+                    })
+                    .collect()
+            }
+            Query::Near(a, b, distance) => {
+                let first = self.analyzer().normalize(a);
+                self.postings
+                    .get(first.as_ref())
+                    .into_iter()
+                    .flat_map(|documents| documents.keys())
+                    .filter(|id| {
+                        let bbow = self.document(id);
+                        bbow.is_some_and(|bbow| !bbow.near(a, b, *distance).is_empty())
+                    })
+                    .collect()
+            }
+            Query::Not(query) => {
+                let excluded = self.evaluate(query);
+                self.documents()
+                    .map(|(id, _)| id)
+                    .filter(|id| !excluded.contains(id))
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators_should_bind_by_precedence() {
+        let query = Query::parse("a OR NOT b c").unwrap();
+        assert_eq!(
+            Query::or(
+                Query::term("a"),
+                Query::and(!Query::term("b"), Query::term("c"))
+            ),
+            query
+        );
+    }
+
+    #[test]
+    fn phrases_and_proximity_should_parse_as_operands() {
+        let query = Query::parse(r#"NOT "big bag"(a NEAR/3 b)"#).unwrap();
+        assert_eq!(
+            Query::and(
+                !Query::Phrase("big bag".to_string()),
+                Query::Near("a".to_string(), "b".to_string(), 3)
+            ),
+            query
+        );
+    }
+
+    #[test]
+    fn malformed_queries_should_report_where() {
+        assert_eq!(Err(ParseError::Empty), Query::parse("  "));
+        assert_eq!(Err(ParseError::UnexpectedEnd), Query::parse("a AND"));
+        assert_eq!(
+            Err(ParseError::Unexpected {
+                token: "OR".to_string(),
+                offset: 2
+            }),
+            Query::parse("( OR b)")
+        );
+        assert_eq!(
+            Err(ParseError::Unclosed { offset: 2 }),
+            Query::parse("a (b")
+        );
+        assert_eq!(
+            Err(ParseError::Unopened { offset: 1 }),
+            Query::parse("a) b")
+        );
+        assert_eq!(
+            Err(ParseError::UnclosedQuote { offset: 2 }),
+            Query::parse("a \"b c")
+        );
+        assert_eq!(
+            Err(ParseError::BadDistance { offset: 2 }),
+            Query::parse("a NEAR/x b")
+        );
+        assert_eq!(
+            Err(ParseError::NearOperand { offset: 9 }),
+            Query::parse("a NEAR/2 (b)")
+        );
+        assert_eq!(
+            Err(ParseError::NearOperand { offset: 0 }),
+            Query::parse("\"a b\" NEAR/2 c")
+        );
+        assert_eq!(
+            Err(ParseError::NearOperand { offset: 0 }),
+            Query::parse("a NEAR/2 b NEAR/2 c")
+        );
+        assert_eq!(
+            Err(ParseError::Unexpected {
+                token: "AND".to_string(),
+                offset: 9
+            }),
+            Query::parse("a NEAR/2 AND b")
+        );
+    }
+
+    #[test]
+    fn not_should_complement_within_the_corpus() {
+        let corpus = Corpus::new()
+            .insert_document('x', "one two")
+            .insert_document('y', "two")
+            .insert_document('z', "three");
+
+        let ids: Vec<_> = corpus.query("NOT ONE").unwrap().into_iter().collect();
+        assert_eq!(vec![&'y', &'z'], ids);
+        let ids: Vec<_> = corpus.query("two NOT one").unwrap().into_iter().collect();
+        assert_eq!(vec![&'y'], ids);
+    }
+}
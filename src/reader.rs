@@ -0,0 +1,254 @@
+//! Streaming ingestion of text from readers.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::analyzer::Token;
+use crate::bytes::after_last_whitespace;
+use crate::{Bbow, WhitespaceTokenizer};
+
+/// The number of bytes requested from a reader at a time.
+const CHUNK: usize = 64 * 1024;
+
+/// Input that is not valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidUtf8 {
+    offset: usize,
+}
+
+impl InvalidUtf8 {
+    pub(crate) fn new(offset: usize) -> Self {
+        Self { offset }
+    }
+
+    /// The byte offset in the input of the first invalid
+    /// (or incomplete) sequence.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for InvalidUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid UTF-8 at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for InvalidUtf8 {}
+
+/// The ways in which reading text into a BBOW can fail.
+#[derive(Debug)]
+pub enum ReadError {
+    /// The reader failed.
+    Io(io::Error),
+    /// The input is not valid UTF-8.
+    Utf8(InvalidUtf8),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "read failed: {error}"),
+            ReadError::Utf8(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(error) => Some(error),
+            ReadError::Utf8(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+impl From<InvalidUtf8> for ReadError {
+    fn from(error: InvalidUtf8) -> Self {
+        ReadError::Utf8(error)
+    }
+}
+
+impl<'a> Bbow<'a> {
+    /// Parse the `text`, which need not outlive this BBOW,
+    /// adding copies of any new words to this BBOW. The
+    /// n-grams continue from the words held in `window`, as
+    /// by [`Bbow::extend_continuing`].
+    pub(crate) fn extend_copied(mut self, text: &str, window: &mut VecDeque<Token<'a>>) -> Self {
+        let scratch = Bbow {
+            analyzer: std::mem::take(&mut self.analyzer),
+            ngram: self.ngram,
+            texts: self.texts.as_ref().map(|_| Vec::new()),
+            ..Bbow::default()
+        };
+        let mut words: VecDeque<Token> = std::mem::take(window);
+        let mut scratch = scratch.extend_continuing(text, &WhitespaceTokenizer, &mut words);
+        *window = words.into_iter().map(Token::into_owned).collect();
+        self.analyzer = std::mem::take(&mut scratch.analyzer);
+        self.merge(&scratch)
+    }
+
+    /// Read UTF-8 text from `reader` to its end, and add
+    /// copies of the valid words contained in it to this
+    /// BBOW. Words are found as by
+    /// [`Bbow::extend_from_text`].
+    ///
+    /// The words and n-grams found are those of the whole
+    /// text. The text is read in chunks, so that it need not
+    /// fit in memory, unless positions are recorded: it is
+    /// then kept whole, as a single text, as by
+    /// [`Bbow::with_positions`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the reader fails, or if the text is not
+    /// valid UTF-8. This BBOW is then left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let mut bbow = Bbow::new();
+    /// bbow.extend_from_reader("Hello world.\nHello again!\n".as_bytes())
+    ///     .unwrap();
+    /// assert_eq!(2, bbow.match_count("hello"));
+    ///
+    /// let error = bbow.extend_from_reader(&b"ok \xff"[..]).unwrap_err();
+    /// assert_eq!("invalid UTF-8 at byte 3", error.to_string());
+    /// assert_eq!(0, bbow.match_count("ok"));
+    /// ```
+    pub fn extend_from_reader<R: Read>(&mut self, mut reader: R) -> Result<(), ReadError> {
+        let mut bbow = self.with_counts(BTreeMap::new());
+        let mut window = VecDeque::new();
+        let mut whole = bbow.texts.is_some().then(String::new);
+        let mut chunk = vec![0; CHUNK];
+        // The text read but not yet parsed, which contains no
+        // whitespace, and the length of its prefix already
+        // known to be valid.
+        let mut buffer = Vec::with_capacity(CHUNK);
+        let mut valid = 0;
+        let mut consumed = 0;
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            };
+            buffer.extend_from_slice(&chunk[..read]);
+            let done = read == 0;
+
+            let fresh = match std::str::from_utf8(&buffer[valid..]) {
+                Ok(text) => text.len(),
+                Err(error) if error.error_len().is_none() && !done => error.valid_up_to(),
+                Err(error) => {
+                    return Err(InvalidUtf8::new(consumed + valid + error.valid_up_to()).into())
+                }
+            };
+            let text = std::str::from_utf8(&buffer[valid..valid + fresh]).expect("validated above");
+            let end = match after_last_whitespace(text) {
+                _ if done => valid + fresh,
+                0 => 0,
+                end => valid + end,
+            };
+            valid += fresh;
+            let text = std::str::from_utf8(&buffer[..end]).expect("validated above");
+            match &mut whole {
+                Some(whole) => whole.push_str(text),
+                None if end > 0 => bbow = bbow.extend_copied(text, &mut window),
+                None => (),
+            }
+
+            buffer.drain(..end);
+            valid -= end;
+            consumed += end;
+            if done {
+                if let Some(whole) = whole {
+                    bbow = bbow.extend_copied(&whole, &mut window);
+                }
+                self.add_bag(&bbow, Cow::clone);
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read all of `reader` into `bbow`.
+    fn read<'a>(mut bbow: Bbow<'a>, reader: impl Read) -> Bbow<'a> {
+        bbow.extend_from_reader(reader).unwrap();
+        bbow
+    }
+
+    /// A reader returning at most a few bytes at a time.
+    struct Trickle<'b>(&'b [u8], usize);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let n = self.1.min(buffer.len()).min(self.0.len());
+            buffer[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn words_and_chars_should_survive_chunk_boundaries() {
+        let text = "naïve café naïve\nœuvre, café";
+        for size in 1..5 {
+            let bbow = read(Bbow::new(), Trickle(text.as_bytes(), size));
+            let expected = Bbow::new().extend_from_text(text);
+            assert_eq!(
+                expected.iter().collect::<Vec<_>>(),
+                bbow.iter().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn ngrams_and_positions_should_survive_chunk_boundaries() {
+        let text = "New York is in new York, and the new york";
+        let rules = || {
+            Bbow::new()
+                .with_ngrams(2)
+                .with_stopwords(["and"].into_iter().collect())
+        };
+        let expected = rules().with_positions().extend_from_text(text);
+        for size in 1..5 {
+            let bbow = read(rules(), Trickle(text.as_bytes(), size));
+            assert_eq!(
+                expected.iter().collect::<Vec<_>>(),
+                bbow.iter().collect::<Vec<_>>()
+            );
+
+            let bbow = read(rules().with_positions(), Trickle(text.as_bytes(), size));
+            assert_eq!(
+                expected.occurrences("new york").collect::<Vec<_>>(),
+                bbow.occurrences("new york").collect::<Vec<_>>()
+            );
+            assert_eq!(Some(text), bbow.text(0));
+        }
+    }
+
+    #[test]
+    fn truncated_input_should_report_its_offset() {
+        let bytes = "ab é".as_bytes();
+        let mut bbow = Bbow::new().with_positions().extend_from_text("ab");
+        let error = bbow
+            .extend_from_reader(&bytes[..bytes.len() - 1])
+            .unwrap_err();
+        assert!(matches!(error, ReadError::Utf8(error) if error.offset() == 3));
+        assert_eq!(1, bbow.match_count("ab"));
+        assert_eq!(None, bbow.text(1));
+    }
+}
@@ -0,0 +1,243 @@
+//! Measures of the similarity of two BBOWs.
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+use crate::{Bbow, Corpus, TfIdf};
+
+/// The sums over the words of two bags needed by the
+/// similarity measures, gathered in a single pass.
+#[derive(Debug, Default)]
+struct Sums {
+    dot: f64,
+    squares_a: f64,
+    squares_b: f64,
+    total_a: f64,
+    total_b: f64,
+    min: f64,
+    max: f64,
+    words_a: usize,
+    words_b: usize,
+    shared: usize,
+}
+
+impl Sums {
+    /// Merge the weights of the words of two bags, each in
+    /// order of its words.
+    fn of<'x, 'y>(
+        a: impl Iterator<Item = (&'x str, f64)>,
+        b: impl Iterator<Item = (&'y str, f64)>,
+    ) -> Self {
+        let mut sums = Sums::default();
+        for (x, y) in Merge(a.peekable(), b.peekable()) {
+            sums.dot += x * y;
+            sums.squares_a += x * x;
+            sums.squares_b += y * y;
+            sums.total_a += x;
+            sums.total_b += y;
+            sums.min += x.min(y);
+            sums.max += x.max(y);
+            sums.words_a += usize::from(x != 0.0);
+            sums.words_b += usize::from(y != 0.0);
+            sums.shared += usize::from(x != 0.0 && y != 0.0);
+        }
+        sums
+    }
+
+    fn cosine(&self) -> f64 {
+        ratio(self.dot, (self.squares_a * self.squares_b).sqrt())
+    }
+}
+
+/// The pairs of weights of each word in either of two
+/// sorted sequences, with 0 for a word missing from one.
+struct Merge<A: Iterator, B: Iterator>(Peekable<A>, Peekable<B>);
+
+impl<'x, 'y, A, B> Iterator for Merge<A, B>
+where
+    A: Iterator<Item = (&'x str, f64)>,
+    B: Iterator<Item = (&'y str, f64)>,
+{
+    type Item = (f64, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let order = match (self.0.peek(), self.1.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((a, _)), Some((b, _))) => a.cmp(b),
+        };
+        Some(match order {
+            Ordering::Less => (self.0.next()?.1, 0.0),
+            Ordering::Greater => (0.0, self.1.next()?.1),
+            Ordering::Equal => (self.0.next()?.1, self.1.next()?.1),
+        })
+    }
+}
+
+/// `numerator / denominator`, or 0 if the denominator is 0.
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+impl<'a> Bbow<'a> {
+    fn sums(&self, other: &Bbow<'_>) -> Sums {
+        let a = self.iter().map(|(word, count)| (word, count as f64));
+        let b = other.iter().map(|(word, count)| (word, count as f64));
+        Sums::of(a, b)
+    }
+
+    /// The cosine of the angle between the vectors of word
+    /// counts of this BBOW and `other`: 1 for bags with
+    /// proportional counts, 0 for bags with no words in
+    /// common. For TF-IDF weights rather than counts, see
+    /// [`Corpus::cosine`].
+    ///
+    /// As with the other measures, words are compared as
+    /// they are stored, so both bags should follow the same
+    /// rules, and an empty bag is similar to nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let a = Bbow::new().extend_from_text("the cat sat");
+    /// let b = Bbow::new().extend_from_text("The cat, the cat, sat sat!");
+    /// assert!((a.cosine(&b) - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn cosine(&self, other: &Bbow<'_>) -> f64 {
+        self.sums(other).cosine()
+    }
+
+    /// The Jaccard index of the sets of words of this BBOW
+    /// and `other`: the number of words in both over the
+    /// number in either.
+    pub fn jaccard(&self, other: &Bbow<'_>) -> f64 {
+        let sums = self.sums(other);
+        let either = sums.words_a + sums.words_b - sums.shared;
+        ratio(sums.shared as f64, either as f64)
+    }
+
+    /// The weighted Jaccard index of this BBOW and `other`:
+    /// the sum over words of the smaller count over the sum
+    /// of the larger.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let a = Bbow::new().extend_from_text("a a a b");
+    /// let b = Bbow::new().extend_from_text("a b b c");
+    /// assert_eq!(2.0 / 3.0, a.jaccard(&b));
+    /// assert_eq!(2.0 / 6.0, a.weighted_jaccard(&b));
+    /// ```
+    pub fn weighted_jaccard(&self, other: &Bbow<'_>) -> f64 {
+        let sums = self.sums(other);
+        ratio(sums.min, sums.max)
+    }
+
+    /// The Sørensen–Dice coefficient of the sets of words
+    /// of this BBOW and `other`: twice the number of words
+    /// in both over the sum of their numbers of words.
+    pub fn dice(&self, other: &Bbow<'_>) -> f64 {
+        let sums = self.sums(other);
+        ratio(
+            2.0 * sums.shared as f64,
+            (sums.words_a + sums.words_b) as f64,
+        )
+    }
+
+    /// The overlap coefficient of the sets of words of this
+    /// BBOW and `other`: the number of words in both over
+    /// the number in the smaller set. A bag whose words all
+    /// appear in the other has overlap 1.
+    pub fn overlap(&self, other: &Bbow<'_>) -> f64 {
+        let sums = self.sums(other);
+        ratio(sums.shared as f64, sums.words_a.min(sums.words_b) as f64)
+    }
+
+    /// The Bray–Curtis dissimilarity of the counts of this
+    /// BBOW and `other`: 0 for bags with the same counts, 1
+    /// for bags with no words in common. Unlike the other
+    /// measures, this is a distance rather than a
+    /// similarity; as an empty bag is similar to nothing,
+    /// it is 1 even between two empty bags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Bbow;
+    /// let a = Bbow::new().extend_from_text("a a b");
+    /// let b = Bbow::new().extend_from_text("a c");
+    /// assert_eq!(0.6, a.bray_curtis(&b));
+    /// assert_eq!(0.0, a.bray_curtis(&a));
+    /// ```
+    pub fn bray_curtis(&self, other: &Bbow<'_>) -> f64 {
+        let sums = self.sums(other);
+        let total = sums.total_a + sums.total_b;
+        if total == 0.0 {
+            return 1.0;
+        }
+        (total - 2.0 * sums.min) / total
+    }
+}
+
+impl<'a, Id: Ord + Clone> Corpus<'a, Id> {
+    /// The cosine of the angle between the TF-IDF vectors
+    /// of the documents `a` and `b`, weighted by the
+    /// `scheme`, or `None` if either is not in this corpus.
+    /// Words found in every document weigh nothing by most
+    /// schemes, so do not make documents similar.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::{Corpus, TfIdf};
+    /// let corpus = Corpus::new()
+    ///     .insert_document(1, "the cat")
+    ///     .insert_document(2, "the dog")
+    ///     .insert_document(3, "the cat");
+    /// let scheme = TfIdf::default();
+    /// assert_eq!(Some(0.0), corpus.cosine(&1, &2, scheme));
+    /// assert_eq!(Some(1.0), corpus.cosine(&1, &3, scheme));
+    /// ```
+    pub fn cosine(&self, a: &Id, b: &Id, scheme: TfIdf) -> Option<f64> {
+        let a = self.tf_idf(a, scheme)?;
+        let b = self.tf_idf(b, scheme)?;
+        let a = a.iter().map(|(word, &weight)| (word.as_ref(), weight));
+        let b = b.iter().map(|(word, &weight)| (word.as_ref(), weight));
+        Some(Sums::of(a, b).cosine())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_measures_should_count_shared_words() {
+        let a = Bbow::new().extend_from_text("a b c d");
+        let b = Bbow::new().extend_from_text("c d e");
+
+        assert_eq!(2.0 / 5.0, a.jaccard(&b));
+        assert_eq!(4.0 / 7.0, a.dice(&b));
+        assert_eq!(2.0 / 3.0, a.overlap(&b));
+        assert!((a.cosine(&b) - 1.0 / 3f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn empty_bags_should_be_similar_to_nothing() {
+        let empty = Bbow::new();
+        let a = Bbow::new().extend_from_text("a");
+
+        assert_eq!(0.0, empty.cosine(&a));
+        assert_eq!(0.0, empty.jaccard(&empty));
+        assert_eq!(0.0, empty.overlap(&a));
+        assert_eq!(1.0, empty.bray_curtis(&a));
+        assert_eq!(1.0, empty.bray_curtis(&empty));
+    }
+}
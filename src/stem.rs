@@ -0,0 +1,413 @@
+//! Stemming of words, so that variants such as "running"
+//! and "runs" share a key.
+//!
+//! The English stemmer is the Porter2 ("Snowball English")
+//! algorithm described at
+//! <https://snowballstem.org/algorithms/english/stemmer.html>.
+
+use std::borrow::Cow;
+
+/// A stemming algorithm.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::Stemmer;
+/// assert_eq!("run", Stemmer::Porter2.stem("running"));
+/// assert_eq!("generous", Stemmer::Porter2.stem("generously"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stemmer {
+    /// The English Porter2 stemmer. It applies only to
+    /// words made of lowercase ASCII letters and
+    /// apostrophes: other words are left unchanged.
+    Porter2,
+}
+
+impl Stemmer {
+    /// Reduce `word` to its stem. When the stem is a prefix
+    /// of the word it is borrowed rather than copied.
+    pub fn stem<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        match self {
+            Stemmer::Porter2 => porter2(word),
+        }
+    }
+}
+
+/// Words with irregular stems, and words that are left
+/// alone.
+const EXCEPTIONS: &[(&str, &str)] = &[
+    ("skis", "ski"),
+    ("skies", "sky"),
+    ("dying", "die"),
+    ("lying", "lie"),
+    ("tying", "tie"),
+    ("idly", "idl"),
+    ("gently", "gentl"),
+    ("ugly", "ugli"),
+    ("early", "earli"),
+    ("only", "onli"),
+    ("singly", "singl"),
+    ("sky", "sky"),
+    ("news", "news"),
+    ("howe", "howe"),
+    ("atlas", "atlas"),
+    ("cosmos", "cosmos"),
+    ("bias", "bias"),
+    ("andes", "andes"),
+];
+
+/// Words that are left alone once step 1a has been applied.
+const STEP_1A_EXCEPTIONS: &[&str] = &[
+    "inning", "outing", "canning", "herring", "earring", "proceed", "exceed", "succeed",
+];
+
+const STEP_2_SUFFIXES: &[(&str, &str)] = &[
+    ("ational", "ate"),
+    ("tional", "tion"),
+    ("enci", "ence"),
+    ("anci", "ance"),
+    ("abli", "able"),
+    ("entli", "ent"),
+    ("izer", "ize"),
+    ("ization", "ize"),
+    ("ation", "ate"),
+    ("ator", "ate"),
+    ("alism", "al"),
+    ("aliti", "al"),
+    ("alli", "al"),
+    ("fulness", "ful"),
+    ("ousli", "ous"),
+    ("ousness", "ous"),
+    ("iveness", "ive"),
+    ("iviti", "ive"),
+    ("biliti", "ble"),
+    ("bli", "ble"),
+    ("ogi", "og"),
+    ("fulli", "ful"),
+    ("lessli", "less"),
+    ("li", ""),
+];
+
+const STEP_3_SUFFIXES: &[(&str, &str)] = &[
+    ("tional", "tion"),
+    ("ational", "ate"),
+    ("alize", "al"),
+    ("icate", "ic"),
+    ("iciti", "ic"),
+    ("ical", "ic"),
+    ("ful", ""),
+    ("ness", ""),
+    ("ative", ""),
+];
+
+const STEP_4_SUFFIXES: &[&str] = &[
+    "al", "ance", "ence", "er", "ic", "able", "ible", "ant", "ement", "ment", "ent", "ism", "ate",
+    "iti", "ous", "ive", "ize", "ion",
+];
+
+fn is_vowel(c: u8) -> bool {
+    matches!(c, b'a' | b'e' | b'i' | b'o' | b'u' | b'y')
+}
+
+fn is_double(c: u8) -> bool {
+    matches!(
+        c,
+        b'b' | b'd' | b'f' | b'g' | b'm' | b'n' | b'p' | b'r' | b't'
+    )
+}
+
+fn is_li_ending(c: u8) -> bool {
+    matches!(
+        c,
+        b'c' | b'd' | b'e' | b'g' | b'h' | b'k' | b'm' | b'n' | b'r' | b't'
+    )
+}
+
+/// Find the start of the region after the first non-vowel
+/// following a vowel, searching from `from`.
+fn region_after(b: &[u8], from: usize) -> usize {
+    (from + 1..b.len())
+        .find(|&i| is_vowel(b[i - 1]) && !is_vowel(b[i]))
+        .map_or(b.len(), |i| i + 1)
+}
+
+/// Does `b` end in a short syllable?
+fn ends_short_syllable(b: &[u8]) -> bool {
+    match b.len() {
+        0 | 1 => false,
+        2 => is_vowel(b[0]) && !is_vowel(b[1]),
+        n => {
+            !is_vowel(b[n - 3])
+                && is_vowel(b[n - 2])
+                && !is_vowel(b[n - 1])
+                && !matches!(b[n - 1], b'w' | b'x' | b'Y')
+        }
+    }
+}
+
+/// A word being stemmed, with its R1 and R2 regions.
+struct Word {
+    b: Vec<u8>,
+    r1: usize,
+    r2: usize,
+}
+
+impl Word {
+    fn ends_with(&self, suffix: &str) -> bool {
+        self.b.ends_with(suffix.as_bytes())
+    }
+
+    /// The start of `suffix`, which must end the word.
+    fn start_of(&self, suffix: &str) -> usize {
+        self.b.len() - suffix.len()
+    }
+
+    fn replace(&mut self, suffix: &str, replacement: &str) {
+        let start = self.start_of(suffix);
+        self.b.truncate(start);
+        self.b.extend_from_slice(replacement.as_bytes());
+    }
+
+    /// The longest of the `suffixes` ending the word.
+    fn longest<'s>(&self, suffixes: impl Iterator<Item = &'s str>) -> Option<&'s str> {
+        suffixes
+            .filter(|suffix| self.ends_with(suffix))
+            .max_by_key(|suffix| suffix.len())
+    }
+
+    fn is_short(&self) -> bool {
+        self.r1 >= self.b.len() && ends_short_syllable(&self.b)
+    }
+
+    fn step_0(&mut self) {
+        if let Some(suffix) = self.longest(["'s'", "'s", "'"].into_iter()) {
+            self.replace(suffix, "");
+        }
+    }
+
+    fn step_1a(&mut self) {
+        let suffixes = ["sses", "ied", "ies", "us", "ss", "s"];
+        match self.longest(suffixes.into_iter()) {
+            Some("sses") => self.replace("sses", "ss"),
+            Some(suffix @ ("ied" | "ies")) => {
+                if self.start_of(suffix) > 1 {
+                    self.replace(suffix, "i");
+                } else {
+                    self.replace(suffix, "ie");
+                }
+            }
+            Some("s") => {
+                let start = self.start_of("s");
+                if start > 1 && self.b[..start - 1].iter().any(|&c| is_vowel(c)) {
+                    self.replace("s", "");
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn step_1b(&mut self) {
+        let suffixes = ["eedly", "ingly", "edly", "eed", "ing", "ed"];
+        match self.longest(suffixes.into_iter()) {
+            Some(suffix @ ("eed" | "eedly")) if self.start_of(suffix) >= self.r1 => {
+                self.replace(suffix, "ee");
+            }
+            Some("eed" | "eedly") | None => (),
+            Some(suffix) => {
+                let start = self.start_of(suffix);
+                if !self.b[..start].iter().any(|&c| is_vowel(c)) {
+                    return;
+                }
+                self.replace(suffix, "");
+                if self.ends_with("at") || self.ends_with("bl") || self.ends_with("iz") {
+                    self.b.push(b'e');
+                } else if self.b.len() >= 2
+                    && self.b[self.b.len() - 1] == self.b[self.b.len() - 2]
+                    && is_double(self.b[self.b.len() - 1])
+                {
+                    self.b.pop();
+                } else if self.is_short() {
+                    self.b.push(b'e');
+                }
+            }
+        }
+    }
+
+    fn step_1c(&mut self) {
+        let n = self.b.len();
+        if n > 2 && matches!(self.b[n - 1], b'y' | b'Y') && !is_vowel(self.b[n - 2]) {
+            self.b[n - 1] = b'i';
+        }
+    }
+
+    fn step_2(&mut self) {
+        let Some(suffix) = self.longest(STEP_2_SUFFIXES.iter().map(|&(suffix, _)| suffix)) else {
+            return;
+        };
+        let start = self.start_of(suffix);
+        if start < self.r1 {
+            return;
+        }
+        let applies = match suffix {
+            "ogi" => start > 0 && self.b[start - 1] == b'l',
+            "li" => start > 0 && is_li_ending(self.b[start - 1]),
+            _ => true,
+        };
+        if applies {
+            let (_, replacement) = STEP_2_SUFFIXES.iter().find(|&&(s, _)| s == suffix).unwrap();
+            self.replace(suffix, replacement);
+        }
+    }
+
+    fn step_3(&mut self) {
+        let Some(suffix) = self.longest(STEP_3_SUFFIXES.iter().map(|&(suffix, _)| suffix)) else {
+            return;
+        };
+        let start = self.start_of(suffix);
+        if start < self.r1 || (suffix == "ative" && start < self.r2) {
+            return;
+        }
+        let (_, replacement) = STEP_3_SUFFIXES.iter().find(|&&(s, _)| s == suffix).unwrap();
+        self.replace(suffix, replacement);
+    }
+
+    fn step_4(&mut self) {
+        let Some(suffix) = self.longest(STEP_4_SUFFIXES.iter().copied()) else {
+            return;
+        };
+        let start = self.start_of(suffix);
+        if start < self.r2 {
+            return;
+        }
+        if suffix == "ion" && !(start > 0 && matches!(self.b[start - 1], b's' | b't')) {
+            return;
+        }
+        self.replace(suffix, "");
+    }
+
+    fn step_5(&mut self) {
+        let n = self.b.len();
+        if self.ends_with("e") {
+            let start = n - 1;
+            if start >= self.r2 || (start >= self.r1 && !ends_short_syllable(&self.b[..start])) {
+                self.b.pop();
+            }
+        } else if self.ends_with("ll") && n > self.r2 {
+            self.b.pop();
+        }
+    }
+}
+
+fn stemmed<'w>(word: &'w str, stem: &str) -> Cow<'w, str> {
+    if word.starts_with(stem) {
+        Cow::from(&word[..stem.len()])
+    } else {
+        Cow::from(stem.to_string())
+    }
+}
+
+fn porter2(word: &str) -> Cow<'_, str> {
+    if word.len() <= 2 || !word.bytes().all(|c| c.is_ascii_lowercase() || c == b'\'') {
+        return Cow::from(word);
+    }
+    if let Some(&(_, stem)) = EXCEPTIONS.iter().find(|&&(w, _)| w == word) {
+        return stemmed(word, stem);
+    }
+
+    let mut b = word.as_bytes().to_vec();
+    if b[0] == b'\'' {
+        b.remove(0);
+    }
+    for i in 0..b.len() {
+        if b[i] == b'y' && (i == 0 || is_vowel(b[i - 1])) {
+            b[i] = b'Y';
+        }
+    }
+
+    let r1 = ["gener", "commun", "arsen"]
+        .iter()
+        .find(|prefix| b.starts_with(prefix.as_bytes()))
+        .map_or_else(|| region_after(&b, 0), |prefix| prefix.len());
+    let r2 = region_after(&b, r1);
+    let mut w = Word { b, r1, r2 };
+
+    w.step_0();
+    w.step_1a();
+    if STEP_1A_EXCEPTIONS
+        .iter()
+        .any(|exception| w.b == exception.as_bytes())
+    {
+        return stemmed(word, std::str::from_utf8(&w.b).unwrap());
+    }
+    w.step_1b();
+    w.step_1c();
+    w.step_2();
+    w.step_3();
+    w.step_4();
+    w.step_5();
+
+    for c in w.b.iter_mut() {
+        if *c == b'Y' {
+            *c = b'y';
+        }
+    }
+    stemmed(word, std::str::from_utf8(&w.b).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porter2_should_match_reference_vocabulary() {
+        let cases = [
+            ("consign", "consign"),
+            ("consigned", "consign"),
+            ("consignment", "consign"),
+            ("consistency", "consist"),
+            ("consistently", "consist"),
+            ("consolation", "consol"),
+            ("consolatory", "consolatori"),
+            ("consoles", "consol"),
+            ("consolidated", "consolid"),
+            ("consolingly", "consol"),
+            ("conspicuously", "conspicu"),
+            ("conspiracy", "conspiraci"),
+            ("conspirators", "conspir"),
+            ("constable", "constabl"),
+            ("generously", "generous"),
+            ("running", "run"),
+            ("runs", "run"),
+            ("hopping", "hop"),
+            ("hoped", "hope"),
+            ("agreed", "agre"),
+            ("cries", "cri"),
+            ("ties", "tie"),
+            ("happy", "happi"),
+            ("skies", "sky"),
+            ("skis", "ski"),
+            ("succeeding", "succeed"),
+            ("knightly", "knight"),
+            ("abilities", "abil"),
+            ("dog's", "dog"),
+        ];
+
+        for (word, stem) in cases {
+            assert_eq!(stem, Stemmer::Porter2.stem(word), "stem of {word}");
+        }
+    }
+
+    #[test]
+    fn prefix_stems_should_be_borrowed() {
+        assert!(matches!(
+            Stemmer::Porter2.stem("running"),
+            Cow::Borrowed("run")
+        ));
+        assert!(matches!(Stemmer::Porter2.stem("happy"), Cow::Owned(_)));
+        assert!(matches!(
+            Stemmer::Porter2.stem("Running"),
+            Cow::Borrowed("Running")
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+//! Stopwords: very common words, such as "the" and "of",
+//! that are left out of a BBOW.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// A language with a built-in stopword list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    French,
+    German,
+    Italian,
+    Portuguese,
+    Spanish,
+}
+
+impl Language {
+    /// The built-in list for this language, one word per
+    /// line.
+    fn list(self) -> &'static str {
+        match self {
+            Language::English => include_str!("stopwords/english.txt"),
+            Language::French => include_str!("stopwords/french.txt"),
+            Language::German => include_str!("stopwords/german.txt"),
+            Language::Italian => include_str!("stopwords/italian.txt"),
+            Language::Portuguese => include_str!("stopwords/portuguese.txt"),
+            Language::Spanish => include_str!("stopwords/spanish.txt"),
+        }
+    }
+}
+
+/// A set of stopwords. Stopwords are compared with words
+/// after normalization (but before stemming), so the set
+/// need not anticipate differences of case.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Bbow, Language, Stopwords};
+/// let bbow = Bbow::new()
+///     .with_stopwords(Stopwords::builtin(Language::English))
+///     .extend_from_text("The cat and the hat.");
+/// assert_eq!(2, bbow.count());
+/// assert_eq!(0, bbow.match_count("the"));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stopwords(BTreeSet<String>);
+
+impl Stopwords {
+    /// Make a new empty stopword set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in stopword list for `language`.
+    pub fn builtin(language: Language) -> Self {
+        Self::from_lines(language.list().lines())
+    }
+
+    /// Read a stopword list with one word per line.
+    /// Surrounding whitespace is ignored, as are blank
+    /// lines and lines starting with `#`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bbow::Stopwords;
+    /// let stopwords = Stopwords::from_reader("# mine\nfoo\n\n  bar\n".as_bytes()).unwrap();
+    /// assert_eq!(2, stopwords.len());
+    /// assert!(stopwords.contains("bar"));
+    /// ```
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let lines = reader.lines().collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::from_lines(lines.iter().map(String::as_str)))
+    }
+
+    /// Read a stopword list from the file at `path`, in the
+    /// format of [`Stopwords::from_reader`].
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    fn from_lines<'l>(lines: impl Iterator<Item = &'l str>) -> Self {
+        lines
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect()
+    }
+
+    /// Add `word` to this set.
+    pub fn insert<S: Into<String>>(&mut self, word: S) {
+        self.0.insert(word.into());
+    }
+
+    /// Is `word` in this set?
+    pub fn contains(&self, word: &str) -> bool {
+        self.0.contains(word)
+    }
+
+    /// The words in this set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// The number of words in this set.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is this set empty?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Stopwords {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<S: Into<String>> Extend<S> for Stopwords {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(Into::into));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_lists_should_load() {
+        for language in [
+            Language::English,
+            Language::French,
+            Language::German,
+            Language::Italian,
+            Language::Portuguese,
+            Language::Spanish,
+        ] {
+            assert!(Stopwords::builtin(language).len() > 100);
+        }
+        assert!(Stopwords::builtin(Language::German).contains("über"));
+    }
+}
@@ -0,0 +1,178 @@
+//! TF-IDF weighting of the words of the documents of a
+//! corpus.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::{Bbow, Corpus};
+
+/// How the number of occurrences of a word in a document
+/// is weighted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TermFrequency {
+    /// The number of occurrences `f`.
+    #[default]
+    Raw,
+    /// `1 + ln f`.
+    Log,
+    /// `0.5 + 0.5 f / m`, where `m` is the number of
+    /// occurrences of the most frequent word of the
+    /// document.
+    Augmented,
+    /// 1 for every word present.
+    Boolean,
+}
+
+impl TermFrequency {
+    fn weight(self, count: usize, max_count: usize) -> f64 {
+        let count = count as f64;
+        match self {
+            TermFrequency::Raw => count,
+            TermFrequency::Log => 1.0 + count.ln(),
+            TermFrequency::Augmented => 0.5 + 0.5 * count / max_count as f64,
+            TermFrequency::Boolean => 1.0,
+        }
+    }
+}
+
+/// How the rarity of a word across the documents is
+/// weighted, given the number of documents `n` and the
+/// number `df` containing the word.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InverseDocumentFrequency {
+    /// `ln(n / df)`.
+    #[default]
+    Standard,
+    /// `1 + ln((1 + n) / (1 + df))`, which is never zero.
+    Smoothed,
+    /// `max(0, ln((n - df + 0.5) / (df + 0.5)))`: the
+    /// log-odds against a document containing the word,
+    /// smoothed so that it stays finite, and taken as 0 for
+    /// words in more than half of the documents.
+    Probabilistic,
+}
+
+impl InverseDocumentFrequency {
+    fn weight(self, documents: usize, frequency: usize) -> f64 {
+        let (n, df) = (documents as f64, frequency as f64);
+        match self {
+            InverseDocumentFrequency::Standard => (n / df).ln(),
+            InverseDocumentFrequency::Smoothed => 1.0 + ((1.0 + n) / (1.0 + df)).ln(),
+            InverseDocumentFrequency::Probabilistic => ((n - df + 0.5) / (df + 0.5)).ln().max(0.0),
+        }
+    }
+}
+
+/// A TF-IDF weighting scheme: the product of a
+/// [`TermFrequency`] and an [`InverseDocumentFrequency`].
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Corpus, TfIdf};
+/// let corpus = Corpus::new()
+///     .insert_document("a", "the cat and the hat")
+///     .insert_document("b", "the dog");
+/// let vector = corpus.tf_idf(&"a", TfIdf::default()).unwrap();
+/// assert_eq!(0.0, vector["the"]);
+/// assert_eq!(2f64.ln(), vector["cat"]);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TfIdf {
+    tf: TermFrequency,
+    idf: InverseDocumentFrequency,
+}
+
+impl TfIdf {
+    /// Make a new weighting scheme from the given variants.
+    pub fn new(tf: TermFrequency, idf: InverseDocumentFrequency) -> Self {
+        Self { tf, idf }
+    }
+
+    /// The term frequency variant of this scheme.
+    pub fn tf(&self) -> TermFrequency {
+        self.tf
+    }
+
+    /// The inverse document frequency variant of this
+    /// scheme.
+    pub fn idf(&self) -> InverseDocumentFrequency {
+        self.idf
+    }
+}
+
+impl<'a, Id: Ord + Clone> Corpus<'a, Id> {
+    /// Weight each word of the given document by the
+    /// `scheme`, returning `None` if the document is not in
+    /// this corpus.
+    pub fn tf_idf(&self, id: &Id, scheme: TfIdf) -> Option<BTreeMap<Cow<'a, str>, f64>> {
+        self.document(id).map(|bbow| self.weigh(bbow, scheme))
+    }
+
+    /// Weight each word of every document by the `scheme`,
+    /// giving one vector per document.
+    pub fn tf_idf_vectors(&self, scheme: TfIdf) -> BTreeMap<Id, BTreeMap<Cow<'a, str>, f64>> {
+        self.documents()
+            .map(|(id, bbow)| (id.clone(), self.weigh(bbow, scheme)))
+            .collect()
+    }
+
+    fn weigh(&self, bbow: &Bbow<'a>, scheme: TfIdf) -> BTreeMap<Cow<'a, str>, f64> {
+        let max_count = bbow.counts.values().copied().max().unwrap_or(0);
+        bbow.counts
+            .iter()
+            .map(|(word, &count)| {
+                let frequency = self.postings.get(word.as_ref()).map_or(0, BTreeMap::len);
+                let weight =
+                    scheme.tf.weight(count, max_count) * scheme.idf.weight(self.len(), frequency);
+                (word.clone(), weight)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_should_follow_their_formulas() {
+        assert_eq!(1.0 + 4f64.ln(), TermFrequency::Log.weight(4, 8));
+        assert_eq!(0.75, TermFrequency::Augmented.weight(4, 8));
+        assert_eq!(1.0, TermFrequency::Boolean.weight(4, 8));
+        assert_eq!(1.0, InverseDocumentFrequency::Smoothed.weight(3, 3));
+        assert_eq!(0.0, InverseDocumentFrequency::Probabilistic.weight(4, 2));
+        assert_eq!(0.0, InverseDocumentFrequency::Probabilistic.weight(4, 3));
+        assert_eq!(
+            (3.5f64 / 1.5).ln(),
+            InverseDocumentFrequency::Probabilistic.weight(4, 1)
+        );
+    }
+
+    #[test]
+    fn words_in_every_document_should_weigh_nothing() {
+        let corpus = Corpus::new()
+            .insert_document(1, "the cat")
+            .insert_document(2, "the cat");
+        let scheme = TfIdf::new(TermFrequency::Raw, InverseDocumentFrequency::Probabilistic);
+
+        let weights = corpus.tf_idf(&1, scheme).unwrap();
+        assert_eq!(0.0, weights["cat"]);
+        assert_eq!(Some(0.0), corpus.cosine(&1, &2, scheme));
+    }
+
+    #[test]
+    fn vectors_should_cover_every_document() {
+        let corpus = Corpus::new()
+            .insert_document(1, "a a b")
+            .insert_document(2, "b c");
+        let scheme = TfIdf::new(TermFrequency::Augmented, InverseDocumentFrequency::Standard);
+
+        let vectors = corpus.tf_idf_vectors(scheme);
+        assert_eq!(2, vectors.len());
+        assert_eq!(2f64.ln(), vectors[&1]["a"]);
+        assert_eq!(0.0, vectors[&2]["b"]);
+        let (key, _) = vectors[&2].first_key_value().unwrap();
+        assert!(matches!(key, Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,72 @@
+//! Tokenizers split a text into the candidate words that
+//! are counted by a [`Bbow`](crate::Bbow).
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Is `c` part of a letter? Combining marks are included,
+/// so that decomposed letters such as "i\u{308}" are not
+/// split from their base.
+pub(crate) fn is_letter(c: char) -> bool {
+    c.is_alphabetic() || is_combining_mark(c)
+}
+
+/// A `Tokenizer` splits a text into a sequence of tokens.
+///
+/// Every token must be a slice of the input text: this is
+/// what allows a BBOW to use the token as a borrowed key
+/// without copying it. Tokens that are not valid words (as
+/// per the rules of BBOW) are discarded when counting, so
+/// a tokenizer need not filter them itself.
+pub trait Tokenizer {
+    /// Split `text` into its sequence of tokens.
+    fn tokens<'t>(&self, text: &'t str) -> impl Iterator<Item = &'t str>;
+}
+
+/// The default tokenizer. Tokens are separated by
+/// whitespace, and leading and trailing non-letters are
+/// trimmed from each token.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Tokenizer, WhitespaceTokenizer};
+/// let tokens: Vec<&str> = WhitespaceTokenizer
+///     .tokens("\"Hello, world!\" it's 42.")
+///     .collect();
+/// assert_eq!(vec!["Hello", "world", "it's", ""], tokens);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokens<'t>(&self, text: &'t str) -> impl Iterator<Item = &'t str> {
+        text.split_whitespace()
+            .map(|word| word.trim_matches(|c| !is_letter(c)))
+    }
+}
+
+/// A tokenizer following the word boundary rules of
+/// [Unicode Standard Annex #29](https://www.unicode.org/reports/tr29/).
+/// Unlike [`WhitespaceTokenizer`], it finds words that are
+/// separated only by punctuation, and splits scripts that
+/// do not use whitespace between words. Segments that
+/// contain no letters or digits are skipped.
+///
+/// # Examples
+///
+/// ```
+/// # use bbow::{Tokenizer, UnicodeWordTokenizer};
+/// let tokens: Vec<&str> = UnicodeWordTokenizer
+///     .tokens("hello,world 東京")
+///     .collect();
+/// assert_eq!(vec!["hello", "world", "東", "京"], tokens);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UnicodeWordTokenizer;
+
+impl Tokenizer for UnicodeWordTokenizer {
+    fn tokens<'t>(&self, text: &'t str) -> impl Iterator<Item = &'t str> {
+        text.unicode_words()
+    }
+}